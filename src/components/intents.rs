@@ -19,6 +19,56 @@ pub struct WantsToUseItem {
     pub target: Option<Point>,
 }
 
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToTakeOut {
+    pub item: Entity,
+    pub container: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToPutIn {
+    pub item: Entity,
+    pub container: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToOpenDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToCloseDoor {
+    pub door: Entity,
+}
+
+/// Looks for hidden traps around the entity, resolved by `SearchSystem`.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct WantsToSearch;
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToBuy {
+    pub item: Entity,
+    pub seller: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToSell {
+    pub item: Entity,
+    pub buyer: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToThrow {
+    pub item: Entity,
+    pub target: Point,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToFire {
+    pub weapon: Entity,
+    pub target: Point,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct WantsToMelee {
     pub target: Entity,
@@ -29,11 +79,27 @@ pub struct WantsToMove {
     pub destination: Point,
 }
 
+/// Moves the entity straight to `destination`, resolved by `MovementSystem`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToTeleport {
+    pub destination: Point,
+}
+
+/// Moves the entity up to `distance` tiles by `direction` each step, resolved by `MovementSystem`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct WantsToBePushed {
+    pub direction: Point,
+    pub distance: i32,
+    pub source: Entity,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum WaitCause {
     Choice,
     Confusion,
     Stun,
+    Sleep,
+    Rest,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -1,12 +1,17 @@
 extern crate rltk;
 extern crate specs_derive;
 
-use rltk::RGB;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use rltk::{DiceType, Point, RGB};
 use serde::{Deserialize, Serialize};
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{ConvertSaveload, Marker};
 
+use crate::FactionKind;
+
 pub use intents::*;
 pub use serialization::*;
 
@@ -52,6 +57,37 @@ pub struct Viewshed {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Monster;
 
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Ally;
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct PackMember {
+    pub leader: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Fleeing {
+    pub turns: i32,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Hearing {
+    pub radius: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Asleep;
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Investigating {
+    pub destination: Point,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Faction {
+    pub kind: FactionKind,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct Name {
     pub name: String,
@@ -60,22 +96,185 @@ pub struct Name {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct BlocksTile;
 
+/// Stops line of sight through the tile the entity stands on.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct BlocksVisibility;
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
     pub defense: i32,
     pub power: i32,
+    /// One in `regen_rate` global turns heals a hit point; zero never regenerates.
+    pub regen_rate: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LootEntry {
+    pub name: String,
+    pub weight: i32,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
-pub struct SuffersDamage {
+pub struct LootTable {
+    pub rolls: i32,
+    pub drop_chance: i32,
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum DamageKind {
+    Physical,
+    Fire,
+    Cold,
+    Poison,
+    Magic,
+}
+
+impl Display for DamageKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            DamageKind::Physical => "physical",
+            DamageKind::Fire => "fire",
+            DamageKind::Cold => "cold",
+            DamageKind::Poison => "poison",
+            DamageKind::Magic => "magic",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DamageInstance {
     pub amount: i32,
+    pub kind: DamageKind,
+    pub source: Option<Entity>,
+    /// What dealt the damage when no entity did, such as lava, so a death can still be explained.
+    pub cause: Option<&'static str>,
+}
+
+/// Every hit an entity takes during a dispatch, so simultaneous sources don't overwrite each other.
+/// Consumed by `DamageSystem` in the same dispatch, so it is never saved.
+#[derive(Component, Clone, Debug)]
+pub struct SuffersDamage {
+    pub instances: Vec<DamageInstance>,
+}
+
+impl SuffersDamage {
+    pub fn new_damage(store: &mut WriteStorage<SuffersDamage>, victim: Entity, amount: i32, kind: DamageKind, source: Option<Entity>) {
+        SuffersDamage::add_instance(store, victim, DamageInstance { amount, kind, source, cause: None });
+    }
+
+    /// Damage from the surroundings rather than from an entity, named by `cause`.
+    pub fn new_hazard_damage(store: &mut WriteStorage<SuffersDamage>, victim: Entity, amount: i32, kind: DamageKind, cause: &'static str) {
+        SuffersDamage::add_instance(store, victim, DamageInstance { amount, kind, source: None, cause: Some(cause) });
+    }
+
+    fn add_instance(store: &mut WriteStorage<SuffersDamage>, victim: Entity, instance: DamageInstance) {
+        if let Some(suffering) = store.get_mut(victim) {
+            suffering.instances.push(instance);
+        } else {
+            store
+                .insert(victim, SuffersDamage { instances: vec![instance] })
+                .expect("Unable to insert damage");
+        }
+    }
+}
+
+/// Who landed the killing blow, left on the victim until `delete_the_dead` hands out kill credit.
+#[derive(Component, Clone, Debug)]
+pub struct SlainBy {
+    pub killer: Option<Entity>,
+    pub cause: Option<&'static str>,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Kills {
+    pub victims: Vec<String>,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Resistances {
+    pub kinds: Vec<DamageKind>,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Vulnerabilities {
+    pub kinds: Vec<DamageKind>,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Item;
 
+/// Holds other items through `InContainer`; chests sit on the map and bags can be carried.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Container;
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct InContainer {
+    pub container: Entity,
+}
+
+/// Lights up the tiles within `radius` that it can see, brightest at its own tile, see `LightingSystem`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct LightSource {
+    pub radius: i32,
+    pub color: RGB,
+}
+
+/// A short-lived light, such as the glare of an explosion, deleted once `turns` runs out.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Flash {
+    pub turns: i32,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum TrapKind {
+    Dart,
+    Teleport,
+    Alarm,
+    ConfusionGas,
+}
+
+/// Springs on whoever steps onto its tile, resolved by `MovementSystem`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Trap {
+    pub kind: TrapKind,
+}
+
+/// Not drawn or shown in tooltips until found by searching or noticed in passing.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Hidden;
+
+/// Blocks movement and sight while closed, through `BlocksTile` and `BlocksVisibility`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Door {
+    pub open: bool,
+}
+
+/// A pile of coins, added to the collector's `Wallet` instead of going into the backpack.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Gold {
+    pub amount: i32,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Wallet {
+    pub gold: i32,
+}
+
+/// What a single one of the item is worth; shopkeepers sell at this price and buy at a fraction of it.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Price {
+    pub value: i32,
+}
+
+/// Trades with the player when bumped into, instead of being attacked.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Shopkeeper;
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct InBackpack {
     pub owner: Entity,
@@ -99,9 +298,143 @@ pub struct Ranged {
     pub range: i32,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum AmmoKind {
+    Arrow,
+    Bolt,
+}
+
+impl Display for AmmoKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            AmmoKind::Arrow => "arrows",
+            AmmoKind::Bolt => "bolts",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub damage: DiceType,
+    pub ammo: AmmoKind,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Ammunition {
+    pub kind: AmmoKind,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Weight {
+    pub weight: f32,
+}
+
+/// Identical items, matched by name, share one entity and count up `quantity` instead.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
+impl Stackable {
+    /// Takes one item off a stack of several, returning false when `item` is the last (or only) one.
+    pub fn split_one(store: &mut WriteStorage<Stackable>, item: Entity) -> bool {
+        match store.get_mut(item) {
+            Some(stackable) if stackable.quantity > 1 => {
+                stackable.quantity -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Lets an item be used again until `current` runs out, instead of being consumed.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Charges {
+    pub current: i32,
+    pub max: i32,
+}
+
+/// Restores the charges of every charged item the reader is carrying.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Recharges;
+
+/// Adds `level` to the attack bonus and damage of an equippable item; negative levels are a hindrance.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Enchantment {
+    pub level: i32,
+}
+
+/// Cursed items cannot be unequipped, dropped or thrown while equipped.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Cursed;
+
+/// The player has learned the item's enchantment and curse, so they are shown in its name.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct ModifiersKnown;
+
+/// Lifts the curse from every item the reader is carrying.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct RemovesCurse;
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Equipped {
+    pub owner: Entity,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub kind: DamageKind,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Resting;
+
+#[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+impl Display for HungerState {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            HungerState::WellFed => "Well Fed",
+            HungerState::Normal => "Normal",
+            HungerState::Hungry => "Hungry",
+            HungerState::Starving => "Starving",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Counts global turns down to the next `HungerState`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct HungerClock {
+    pub state: HungerState,
+    pub duration: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct ProvidesFood;
+
+/// Shoves whatever the item or attacker hits this many tiles away from the source.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Knockback {
+    pub distance: i32,
+}
+
+/// Dice rolled when the item hits something after being thrown, instead of the default 1d2.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct ThrowDamage {
+    pub damage: DiceType,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -114,6 +447,36 @@ pub struct Confusion {
     pub turns: i32,
 }
 
+/// Shows the location of every monster on the map, in or out of view, until `turns` runs out.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Telepathy {
+    pub turns: i32,
+}
+
+/// Reveals the whole map to the reader.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct MagicMapping;
+
+/// Moves the reader to a random free floor tile.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Teleports;
+
+/// Grants the reader `Telepathy` for `turns`.
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct DetectsMonsters {
+    pub turns: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct SummonsAlly;
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Charms;
+
+/// Reveals the true names of every unidentified item the reader is carrying.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Identifies;
+
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct WantsToTakeTurn;
 
@@ -136,6 +499,7 @@ pub struct CanMove {
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct CanMelee {
     pub time_cost: u32,
+    pub damage: DiceType,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-use crate::Map;
+use crate::{MagicItemNames, Map};
 
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct SerializeMe;
 
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct SerializationHelper {
-    pub map: Map
+    pub map: Map,
+    pub magic_item_names: MagicItemNames,
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Eq, Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum FactionKind {
+    Player,
+    Orc,
+    Goblin,
+    Merchant,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Reaction {
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+/// How an entity of faction `from` feels about an entity of faction `to`.
+pub fn get_reaction(from: FactionKind, to: FactionKind) -> Reaction {
+    match (from, to) {
+        (from, to) if from == to => Reaction::Friendly,
+        (FactionKind::Merchant, _) | (_, FactionKind::Merchant) => Reaction::Neutral,
+        (FactionKind::Player, _) | (_, FactionKind::Player) => Reaction::Hostile,
+        (FactionKind::Orc, FactionKind::Goblin) | (FactionKind::Goblin, FactionKind::Orc) => Reaction::Hostile,
+        _ => Reaction::Neutral,
+    }
+}
+
+pub fn is_hostile(from: FactionKind, to: FactionKind) -> bool {
+    get_reaction(from, to) == Reaction::Hostile
+}
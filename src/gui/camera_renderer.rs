@@ -1,12 +1,14 @@
 use rltk::{Algorithm2D, ColorPair, Console, Point, RGB};
 use specs::prelude::*;
 
-use crate::{CONSOLE_INDEX, Context, DEBUG, GAME_LOG_HEIGHT, Map, Position, Renderable, RenderAura, RenderBackground, TileType};
+use crate::{CONSOLE_INDEX, Context, DEBUG, GAME_LOG_HEIGHT, Hidden, Map, Monster, Position, Renderable, RenderAura, RenderBackground, Telepathy, TileType};
 
 const SHOW_BOUNDARIES: bool = DEBUG;
 const WALL_HEIGHT: usize = 4;
 const ENTITY_HEIGHT: usize = 2;
 const AURA_HEIGHT: usize = ENTITY_HEIGHT + 1;
+/// Dimly lit tiles that can still be seen are drawn at least this bright.
+const MIN_LIT_BRIGHTNESS: f32 = 0.35;
 
 pub struct CameraRenderer<'a, 'b> {
     pub ecs: &'a World,
@@ -70,19 +72,23 @@ impl<'a, 'b> CameraRenderer<'a, 'b> {
     fn draw_entities(&mut self, min_x: i32, min_y: i32) {
         let positions = self.ecs.read_storage::<Position>();
         let renderables = self.ecs.read_storage::<Renderable>();
+        let monsters = self.ecs.read_storage::<Monster>();
+        let hidden = self.ecs.read_storage::<Hidden>();
         let map = self.ecs.fetch::<Map>();
+        let player_entity = self.ecs.fetch::<Entity>();
+        let is_telepathic = self.ecs.read_storage::<Telepathy>().contains(*player_entity);
 
         let (map_width, map_height) = (map.width - 1, map.height - 1);
 
-        let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
+        let mut data = (&positions, &renderables, monsters.maybe(), !&hidden).join().collect::<Vec<_>>();
         data.sort_by(|a, b| {
-            let (_, a_render) = a;
-            let (_, b_render) = b;
+            let (_, a_render, _, _) = a;
+            let (_, b_render, _, _) = b;
             b_render.render_order.cmp(&a_render.render_order)
         });
 
-        for (position, renderable) in data.iter() {
-            if map.is_visible(position.x, position.y) {
+        for (position, renderable, monster, _) in data.iter() {
+            if map.is_visible(position.x, position.y) || (is_telepathic && monster.is_some()) {
                 let entity_screen_x = position.x - min_x;
                 let entity_screen_y = position.y - min_y;
                 //TODO check to see if this can be replaced with a map method call
@@ -214,6 +220,13 @@ fn is_revealed_wall(map: &Map, x: i32, y: i32) -> bool {
     return true;
 }
 
+/// Tints a colour by the light falling on its tile, so torchlight warms what it touches and shadows darken it.
+fn apply_light(color: RGB, light: RGB) -> RGB {
+    let channel = |color: f32, light: f32| color * light.clamp(MIN_LIT_BRIGHTNESS, 1.0);
+
+    RGB::from_f32(channel(color.r, light.r), channel(color.g, light.g), channel(color.b, light.b))
+}
+
 fn get_tile_glyph(idx: usize, map: &Map) -> (u8, RGB, RGB, bool) {
     let pt = map.index_to_point2d(idx);
     let tile = map.tiles[idx];
@@ -232,12 +245,37 @@ fn get_tile_glyph(idx: usize, map: &Map) -> (u8, RGB, RGB, bool) {
                 fg = RGB::from_f32(0.0, 1.0, 0.0);
                 glyph = rltk::to_cp437(get_wall_glyph(&map, pt.x, pt.y));
             }
+            TileType::ShallowWater => {
+                fg = RGB::from_f32(0.3, 0.6, 1.0);
+                glyph = rltk::to_cp437('~')
+            }
+            TileType::DeepWater => {
+                fg = RGB::from_f32(0.1, 0.2, 0.9);
+                glyph = rltk::to_cp437('≈')
+            }
+            TileType::Lava => {
+                fg = RGB::from_f32(1.0, 0.35, 0.0);
+                glyph = rltk::to_cp437('≈')
+            }
+            TileType::Rubble => {
+                fg = RGB::from_f32(0.6, 0.5, 0.4);
+                glyph = rltk::to_cp437(';')
+            }
+            TileType::Grass => {
+                fg = RGB::from_f32(0.2, 0.8, 0.2);
+                glyph = rltk::to_cp437('"')
+            }
+            TileType::Bridge => {
+                fg = RGB::named(rltk::CHOCOLATE);
+                glyph = rltk::to_cp437('=')
+            }
         }
     }
 
     if !map.visible_tiles[idx] {
         fg = fg.to_greyscale();
     } else {
+        fg = apply_light(fg, map.light[idx]);
         bg = RGB::named(rltk::BLACK);
     }
 
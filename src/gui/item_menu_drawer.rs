@@ -2,33 +2,144 @@ extern crate rltk;
 
 use specs::prelude::*;
 
-use crate::{CONSOLE_INDEX, Context, InBackpack, Name, State};
+use crate::{carried_weight, carry_capacity, Charges, CombatStats, CONSOLE_INDEX, Container, Context, Cursed, Enchantment, Equipped, InBackpack, InContainer, MagicItemNames, ModifiersKnown, Name, Price, Stackable, State, TradeMode, Wallet, with_modifiers};
 
 use self::rltk::{ColorPair, Point, Rect, RGB, VirtualKeyCode};
 
+const CANCEL_FOOTER: &str = "ESCAPE to cancel";
+const SWITCH_FOOTER: &str = "TAB to switch, ESCAPE to cancel";
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult { Cancel, NoResponse, Selected(Entity) }
 
 pub fn show_inventory(state: &mut State, context: &mut Context) -> ItemMenuResult {
+    let player_entity = *state.ecs.fetch::<Entity>();
+
     ItemMenuDrawer {
         state,
         context,
         settings: ItemMenuDrawerSettings {
             title: "Inventory",
+            footer: CANCEL_FOOTER,
+            source: ItemSource::Backpack(player_entity),
+            show_weight: true,
+            show_contents: true,
+            hide_containers: false,
+            trade: None,
         },
     }.show_item_selection_menu()
 }
 
 pub fn show_drop_item_menu(state: &mut State, context: &mut Context) -> ItemMenuResult {
+    let player_entity = *state.ecs.fetch::<Entity>();
+
     ItemMenuDrawer {
         state,
         context,
         settings: ItemMenuDrawerSettings {
             title: "Drop which item?",
+            footer: CANCEL_FOOTER,
+            source: ItemSource::Backpack(player_entity),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: false,
+            trade: None,
+        },
+    }.show_item_selection_menu()
+}
+
+pub fn show_throw_item_menu(state: &mut State, context: &mut Context) -> ItemMenuResult {
+    let player_entity = *state.ecs.fetch::<Entity>();
+
+    ItemMenuDrawer {
+        state,
+        context,
+        settings: ItemMenuDrawerSettings {
+            title: "Throw which item?",
+            footer: CANCEL_FOOTER,
+            source: ItemSource::Backpack(player_entity),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: false,
+            trade: None,
+        },
+    }.show_item_selection_menu()
+}
+
+pub fn show_buy_item_menu(state: &mut State, context: &mut Context, shopkeeper: Entity) -> ItemMenuResult {
+    ItemMenuDrawer {
+        state,
+        context,
+        settings: ItemMenuDrawerSettings {
+            title: "Buy which item?",
+            footer: SWITCH_FOOTER,
+            source: ItemSource::Backpack(shopkeeper),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: false,
+            trade: Some(TradeMode::Buy),
+        },
+    }.show_item_selection_menu()
+}
+
+pub fn show_sell_item_menu(state: &mut State, context: &mut Context) -> ItemMenuResult {
+    let player_entity = *state.ecs.fetch::<Entity>();
+
+    ItemMenuDrawer {
+        state,
+        context,
+        settings: ItemMenuDrawerSettings {
+            title: "Sell which item?",
+            footer: SWITCH_FOOTER,
+            source: ItemSource::Backpack(player_entity),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: false,
+            trade: Some(TradeMode::Sell),
+        },
+    }.show_item_selection_menu()
+}
+
+pub fn show_container_menu(state: &mut State, context: &mut Context, container: Entity) -> ItemMenuResult {
+    ItemMenuDrawer {
+        state,
+        context,
+        settings: ItemMenuDrawerSettings {
+            title: "Take out which item?",
+            footer: SWITCH_FOOTER,
+            source: ItemSource::Container(container),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: false,
+            trade: None,
         },
     }.show_item_selection_menu()
 }
 
+pub fn show_put_in_container_menu(state: &mut State, context: &mut Context) -> ItemMenuResult {
+    let player_entity = *state.ecs.fetch::<Entity>();
+
+    ItemMenuDrawer {
+        state,
+        context,
+        settings: ItemMenuDrawerSettings {
+            title: "Put in which item?",
+            footer: SWITCH_FOOTER,
+            source: ItemSource::Backpack(player_entity),
+            show_weight: false,
+            show_contents: false,
+            hide_containers: true,
+            trade: None,
+        },
+    }.show_item_selection_menu()
+}
+
+#[derive(Copy, Clone)]
+enum ItemSource {
+    Backpack(Entity),
+    Container(Entity),
+}
+
 struct ItemMenuDrawer<'a, 'b> {
     state: &'a mut State,
     context: &'a mut Context<'b>,
@@ -37,6 +148,14 @@ struct ItemMenuDrawer<'a, 'b> {
 
 struct ItemMenuDrawerSettings<'a> {
     pub title: &'a str,
+    pub footer: &'a str,
+    pub source: ItemSource,
+    pub show_weight: bool,
+    /// Lists what is inside each carried container, below it.
+    pub show_contents: bool,
+    pub hide_containers: bool,
+    /// Lists only priced items, with what they would be bought or sold for.
+    pub trade: Option<TradeMode>,
 }
 
 impl<'a, 'b> ItemMenuDrawer<'a, 'b> {
@@ -46,14 +165,70 @@ impl<'a, 'b> ItemMenuDrawer<'a, 'b> {
         let player_entity = self.state.ecs.fetch::<Entity>();
         let names = self.state.ecs.read_storage::<Name>();
         let in_backpacks = self.state.ecs.read_storage::<InBackpack>();
+        let equipped = self.state.ecs.read_storage::<Equipped>();
+        let stackables = self.state.ecs.read_storage::<Stackable>();
+        let charges = self.state.ecs.read_storage::<Charges>();
+        let modifiers_known = self.state.ecs.read_storage::<ModifiersKnown>();
+        let enchantments = self.state.ecs.read_storage::<Enchantment>();
+        let cursed = self.state.ecs.read_storage::<Cursed>();
+        let magic_item_names = self.state.ecs.fetch::<MagicItemNames>();
+        let prices = self.state.ecs.read_storage::<Price>();
+        let in_containers = self.state.ecs.read_storage::<InContainer>();
+        let containers = self.state.ecs.read_storage::<Container>();
+        let source = self.settings.source;
+        let trade = self.settings.trade;
         let entities = self.state.ecs.entities();
 
-        let inventory_count = in_backpacks
+        let describe = |entity: Entity, name: &Name| -> String {
+            let mut label = magic_item_names.display_name(&name.name);
+            if modifiers_known.contains(entity) {
+                label = with_modifiers(label, enchantments.get(entity), cursed.contains(entity));
+            }
+            if let Some(stackable) = stackables.get(entity).filter(|stackable| stackable.quantity > 1) {
+                label = format!("{} (x{})", label, stackable.quantity);
+            }
+            if let Some(charges) = charges.get(entity) {
+                label = format!("{} ({}/{})", label, charges.current, charges.max);
+            }
+            if equipped.get(entity).is_some() {
+                label = format!("{} (equipped)", label);
+            }
+            if let (Some(trade), Some(price)) = (trade, prices.get(entity)) {
+                label = format!("{} - {} gold", label, trade.price_of(price, stackables.get(entity)));
+            }
+            label
+        };
+
+        let contents_of = |container: Entity| {
+            (&entities, &names, &in_containers)
+                .join()
+                .filter(move |(_, _, in_container)| in_container.container == container)
+                .map(|(entity, name, _)| (entity, name))
+        };
+
+        let listed = (&entities, &names)
             .join()
-            .filter(|in_backpack| {
-                in_backpack.owner == *player_entity
+            .filter(|(entity, _)| match source {
+                ItemSource::Backpack(owner) => in_backpacks.get(*entity).is_some_and(|in_backpack| in_backpack.owner == owner),
+                ItemSource::Container(container) => in_containers.get(*entity).is_some_and(|in_container| in_container.container == container),
             })
-            .count();
+            .filter(|(entity, _)| trade.is_none() || prices.contains(*entity))
+            .filter(|(entity, _)| !self.settings.hide_containers || !containers.contains(*entity))
+            .collect::<Vec<(Entity, &Name)>>();
+
+        // Selectable rows carry their entity; container contents are listed under them without a hotkey.
+        let mut rows: Vec<(Option<Entity>, String)> = Vec::new();
+        for (entity, name) in listed {
+            rows.push((Some(entity), describe(entity, name)));
+
+            if self.settings.show_contents && containers.contains(entity) {
+                for (content, content_name) in contents_of(entity) {
+                    rows.push((None, format!("- {}", describe(content, content_name))));
+                }
+            }
+        }
+
+        let inventory_count = rows.len();
 
         let (window_width, window_height) = self.context.get_screen_size();
 
@@ -62,7 +237,7 @@ impl<'a, 'b> ItemMenuDrawer<'a, 'b> {
         let highlight_fg = RGB::named(rltk::YELLOW);
         let plain_fg = RGB::named(rltk::WHITE);
 
-        const INVENTORY_WIDTH: i32 = 31;
+        const INVENTORY_WIDTH: i32 = 41;
         const BORDER_TEXT_OFFSET: i32 = 3;
 
 
@@ -81,34 +256,63 @@ impl<'a, 'b> ItemMenuDrawer<'a, 'b> {
                 highlight_fg,
                 bg));
 
+        if self.settings.show_weight {
+            let capacity = self.state.ecs.read_storage::<CombatStats>().get(*player_entity).map_or(0., carry_capacity);
+            let weight = format!("{:.1}/{:.1} lbs", carried_weight(&self.state.ecs, *player_entity), capacity);
+
+            self.context.print_color(
+                Point::new(
+                    inventory_x + INVENTORY_WIDTH - BORDER_TEXT_OFFSET - weight.len() as i32,
+                    y - 2),
+                &weight,
+                ColorPair::new(
+                    highlight_fg,
+                    bg));
+        }
+
+        if trade.is_some() {
+            let gold = format!("{} gold", self.state.ecs.read_storage::<Wallet>().get(*player_entity).map_or(0, |wallet| wallet.gold));
+
+            self.context.print_color(
+                Point::new(
+                    inventory_x + INVENTORY_WIDTH - BORDER_TEXT_OFFSET - gold.len() as i32,
+                    y - 2),
+                &gold,
+                ColorPair::new(
+                    highlight_fg,
+                    bg));
+        }
+
         self.context.print_color(
             Point::new(
                 inventory_x + BORDER_TEXT_OFFSET,
                 y + inventory_count as i32 + 1),
-            "ESCAPE to cancel",
+            self.settings.footer,
             ColorPair::new(
                 highlight_fg,
                 bg));
 
-        let inventory = (&names, &in_backpacks, &entities)
-            .join()
-            .filter(|(_, in_backpack, _)| {
-                in_backpack.owner == *player_entity
-            });
-
         let mut hotkey = 'a' as u8;
         let mut selectable_items: Vec<Entity> = Vec::new();
 
-        for (name, _in_backpack, entity) in inventory {
-            self.context.set(Point::new(inventory_x + 2, y), ColorPair::new(plain_fg, bg), rltk::to_cp437('('));
-            self.context.set(Point::new(inventory_x + 3, y), ColorPair::new(highlight_fg, bg), hotkey);
-            self.context.set(Point::new(inventory_x + 4, y), ColorPair::new(plain_fg, bg), rltk::to_cp437(')'));
+        for (entity, label) in rows.iter() {
+            match entity {
+                Some(entity) => {
+                    self.context.set(Point::new(inventory_x + 2, y), ColorPair::new(plain_fg, bg), rltk::to_cp437('('));
+                    self.context.set(Point::new(inventory_x + 3, y), ColorPair::new(highlight_fg, bg), hotkey);
+                    self.context.set(Point::new(inventory_x + 4, y), ColorPair::new(plain_fg, bg), rltk::to_cp437(')'));
 
-            self.context.print_color(Point::new(inventory_x + 6, y), &name.name, ColorPair::new(plain_fg, bg));
+                    self.context.print_color(Point::new(inventory_x + 6, y), label, ColorPair::new(plain_fg, bg));
+
+                    selectable_items.push(*entity);
+                    hotkey += 1;
+                }
+                None => {
+                    self.context.print_color(Point::new(inventory_x + 8, y), label, ColorPair::new(RGB::named(rltk::GREY), bg));
+                }
+            }
 
-            selectable_items.push(entity);
             y += 1;
-            hotkey += 1;
         }
         self.context.set_target(CONSOLE_INDEX.base);
 
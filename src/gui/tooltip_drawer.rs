@@ -4,7 +4,7 @@ use std::string::ToString;
 
 use specs::prelude::*;
 
-use crate::{CONSOLE_INDEX, Context, get_screen_bounds, Map, Name, Position};
+use crate::{Ally, Asleep, CONSOLE_INDEX, Context, Cursed, Enchantment, get_screen_bounds, Hidden, MagicItemNames, Map, ModifiersKnown, Name, Position, with_modifiers};
 
 use self::rltk::{ColorPair, Point, RGB};
 
@@ -31,15 +31,33 @@ impl<'a, 'b> TooltipDrawer<'a, 'b> {
         let map = self.ecs.fetch::<Map>();
         let names = self.ecs.read_storage::<Name>();
         let positions = self.ecs.read_storage::<Position>();
+        let allies = self.ecs.read_storage::<Ally>();
+        let asleep = self.ecs.read_storage::<Asleep>();
+        let magic_item_names = self.ecs.fetch::<MagicItemNames>();
+        let modifiers_known = self.ecs.read_storage::<ModifiersKnown>();
+        let enchantments = self.ecs.read_storage::<Enchantment>();
+        let cursed = self.ecs.read_storage::<Cursed>();
+        let hidden = self.ecs.read_storage::<Hidden>();
+        let entities = self.ecs.entities();
 
         if !map.is_valid(map_x, map_y) || !map.is_visible(map_x, map_y) {
             return;
         }
 
         let mut tooltip: Vec<String> = Vec::new();
-        for (name, position) in (&names, &positions).join() {
+        for (entity, name, position, _) in (&entities, &names, &positions, !&hidden).join() {
             if position.x == map_x && position.y == map_y {
-                tooltip.insert(0, name.name.to_string());
+                let mut line = magic_item_names.display_name(&name.name);
+                if modifiers_known.contains(entity) {
+                    line = with_modifiers(line, enchantments.get(entity), cursed.contains(entity));
+                }
+                if allies.get(entity).is_some() {
+                    line.push_str(" (friendly)");
+                }
+                if asleep.get(entity).is_some() {
+                    line.push_str(" (asleep)");
+                }
+                tooltip.insert(0, line);
             }
         }
 
@@ -2,7 +2,7 @@ extern crate rltk;
 
 use specs::prelude::*;
 
-use crate::{CombatStats, Context, GameLog, Player, TooltipDrawer, TooltipOrientation};
+use crate::{CombatStats, Context, GameLog, HungerClock, HungerState, Player, TooltipDrawer, TooltipOrientation, Wallet};
 
 use self::rltk::{ColorPair, Point, Rect, RGB};
 
@@ -10,6 +10,8 @@ pub const GAME_LOG_HEIGHT: i32 = 7;
 const HEALTH_TEXT_OFFSET: i32 = 12;
 const HEALTH_BAR_START: i32 = 28;
 const LOG_ENTRY_OFFSET: i32 = 2;
+const HUNGER_TEXT_OFFSET: i32 = 14;
+const GOLD_TEXT_OFFSET: i32 = 2;
 
 pub fn draw_ui(ecs: &World, context: &mut Context) {
     let (window_width, window_height) = context.get_screen_size();
@@ -41,6 +43,8 @@ impl<'a, 'b> UiDrawer<'a, 'b> {
     pub fn draw_ui(&mut self) {
         self.draw_game_log_frame();
         self.draw_health();
+        self.draw_hunger();
+        self.draw_gold();
         self.draw_logs();
         self.draw_mouse_cursor();
         self.draw_tooltip();
@@ -88,6 +92,51 @@ impl<'a, 'b> UiDrawer<'a, 'b> {
         }
     }
 
+    fn draw_gold(&mut self) {
+        let wallets = self.ecs.read_storage::<Wallet>();
+        let players = self.ecs.read_storage::<Player>();
+
+        for (_player, wallet) in (&players, &wallets).join() {
+            let gold = format!(" Gold: {} ", wallet.gold);
+
+            self.context.print_color(
+                Point::new(
+                    GOLD_TEXT_OFFSET,
+                    self.dimensions.window_height - 1),
+                &gold,
+                ColorPair::new(
+                    RGB::named(rltk::GOLD),
+                    RGB::named(rltk::BLACK)),
+            );
+        }
+    }
+
+    fn draw_hunger(&mut self) {
+        let hunger_clocks = self.ecs.read_storage::<HungerClock>();
+        let players = self.ecs.read_storage::<Player>();
+
+        for (_player, hunger_clock) in (&players, &hunger_clocks).join() {
+            let color = match hunger_clock.state {
+                HungerState::WellFed => RGB::named(rltk::GREEN),
+                HungerState::Normal => continue,
+                HungerState::Hungry => RGB::named(rltk::ORANGE),
+                HungerState::Starving => RGB::named(rltk::RED),
+            };
+
+            let hunger = format!(" {} ", hunger_clock.state);
+
+            self.context.print_color(
+                Point::new(
+                    self.dimensions.window_width - HUNGER_TEXT_OFFSET,
+                    self.dimensions.window_height - 1),
+                &hunger,
+                ColorPair::new(
+                    color,
+                    RGB::named(rltk::BLACK)),
+            );
+        }
+    }
+
     fn draw_logs(&mut self) {
         let log = self.ecs.fetch::<GameLog>();
 
@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Enchantment, RNG};
+
+const SCROLL_SYLLABLES: [&str; 16] = [
+    "ZELGO", "MER", "FOO", "BAR", "XIXAXA", "KIRJE", "ELBIB", "YUM",
+    "VERR", "NOSIL", "THARR", "ANDOVA", "ULY", "PRATYAVAYAH", "DAIYEN", "ASHPD",
+];
+
+const POTION_APPEARANCES: [&str; 12] = [
+    "Murky", "Bubbling", "Fizzy", "Smoky", "Cloudy", "Glowing",
+    "Oily", "Milky", "Swirly", "Dark", "Effervescent", "Viscous",
+];
+
+const WAND_APPEARANCES: [&str; 10] = [
+    "Oak", "Ebony", "Bone", "Crystal", "Copper",
+    "Iron", "Glass", "Marble", "Willow", "Jade",
+];
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum MagicItemKind {
+    Scroll,
+    Potion,
+    Wand,
+}
+
+/// The unidentified appearance of every magic item in this run, and which of them the player has learned.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MagicItemNames {
+    obfuscated: HashMap<String, String>,
+    identified: HashSet<String>,
+}
+
+impl MagicItemNames {
+    pub fn new() -> MagicItemNames {
+        MagicItemNames::default()
+    }
+
+    /// Rolls an appearance for the named item the first time it is seen in this run.
+    pub fn register(&mut self, name: &str, kind: MagicItemKind) {
+        if self.obfuscated.contains_key(name) {
+            return;
+        }
+
+        let appearance = loop {
+            let appearance = match kind {
+                MagicItemKind::Scroll => format!("Scroll of {} {}", random_syllable(), random_syllable()),
+                MagicItemKind::Potion => format!("{} Potion", random_appearance(&POTION_APPEARANCES)),
+                MagicItemKind::Wand => format!("{} Wand", random_appearance(&WAND_APPEARANCES)),
+            };
+
+            if !self.obfuscated.values().any(|taken| *taken == appearance) {
+                break appearance;
+            }
+        };
+
+        self.obfuscated.insert(name.to_string(), appearance);
+    }
+
+    /// Marks the named item as known, returning true if it was not known before.
+    pub fn identify(&mut self, name: &str) -> bool {
+        self.obfuscated.contains_key(name) && self.identified.insert(name.to_string())
+    }
+
+    /// The name the player knows the item by: its true name once identified, otherwise its appearance.
+    pub fn display_name(&self, name: &str) -> String {
+        match self.obfuscated.get(name) {
+            Some(appearance) if !self.identified.contains(name) => appearance.to_string(),
+            _ => name.to_string(),
+        }
+    }
+}
+
+/// Adds the enchantment level and curse to an item's name, for items whose modifiers are known.
+pub fn with_modifiers(name: String, enchantment: Option<&Enchantment>, is_cursed: bool) -> String {
+    let mut name = match enchantment {
+        Some(enchantment) => format!("{:+} {}", enchantment.level, name),
+        None => name,
+    };
+
+    if is_cursed {
+        name.push_str(" (cursed)");
+    }
+
+    name
+}
+
+fn random_syllable() -> &'static str {
+    random_appearance(&SCROLL_SYLLABLES)
+}
+
+fn random_appearance(appearances: &[&'static str]) -> &'static str {
+    appearances[RNG.range(0, appearances.len() as i32) as usize]
+}
@@ -13,9 +13,12 @@ use specs::WorldExt;
 
 pub use components::*;
 pub use context::*;
+pub use faction::*;
 pub use game_log::*;
 pub use gui::*;
+pub use identification::*;
 pub use map::*;
+pub use morgue::*;
 pub use player::*;
 pub use random::*;
 pub use save_load_system::*;
@@ -35,6 +38,9 @@ mod spawner;
 mod gui;
 mod game_log;
 mod context;
+mod faction;
+mod identification;
+mod morgue;
 mod turn_decider;
 mod save_load_system;
 
@@ -52,28 +58,89 @@ fn main() {
     state.ecs.insert(RunStateHolder { run_state: RunState::PreRun });
     state.ecs.insert(GameLog::new_with_first_log(format!("Welcome to {}", TITLE)));
     state.ecs.insert(ParticleBuilder::new());
+    state.ecs.insert(SpawnBuilder::new());
+    state.ecs.insert(NoiseBuilder::new());
+    state.ecs.insert(MagicItemNames::new());
 
     state.ecs.register::<Position>();
     state.ecs.register::<Renderable>();
     state.ecs.register::<Player>();
     state.ecs.register::<Viewshed>();
     state.ecs.register::<Monster>();
+    state.ecs.register::<Faction>();
+    state.ecs.register::<Ally>();
+    state.ecs.register::<PackMember>();
+    state.ecs.register::<Fleeing>();
+    state.ecs.register::<Hearing>();
+    state.ecs.register::<Asleep>();
+    state.ecs.register::<Investigating>();
     state.ecs.register::<Name>();
     state.ecs.register::<BlocksTile>();
     state.ecs.register::<CombatStats>();
+    state.ecs.register::<LootTable>();
     state.ecs.register::<WantsToMelee>();
     state.ecs.register::<SuffersDamage>();
+    state.ecs.register::<SlainBy>();
+    state.ecs.register::<Kills>();
+    state.ecs.register::<Resistances>();
+    state.ecs.register::<Vulnerabilities>();
     state.ecs.register::<Item>();
     state.ecs.register::<InBackpack>();
     state.ecs.register::<WantsToPickUp>();
     state.ecs.register::<WantsToUseItem>();
     state.ecs.register::<WantsToDrop>();
+    state.ecs.register::<WantsToThrow>();
+    state.ecs.register::<ThrowDamage>();
+    state.ecs.register::<Knockback>();
+    state.ecs.register::<Resting>();
+    state.ecs.register::<HungerClock>();
+    state.ecs.register::<ProvidesFood>();
+    state.ecs.register::<WantsToBePushed>();
+    state.ecs.register::<WantsToFire>();
+    state.ecs.register::<RangedWeapon>();
+    state.ecs.register::<Ammunition>();
+    state.ecs.register::<Stackable>();
+    state.ecs.register::<Charges>();
+    state.ecs.register::<Recharges>();
+    state.ecs.register::<Enchantment>();
+    state.ecs.register::<Cursed>();
+    state.ecs.register::<ModifiersKnown>();
+    state.ecs.register::<RemovesCurse>();
+    state.ecs.register::<Telepathy>();
+    state.ecs.register::<MagicMapping>();
+    state.ecs.register::<Teleports>();
+    state.ecs.register::<DetectsMonsters>();
+    state.ecs.register::<WantsToTeleport>();
+    state.ecs.register::<Gold>();
+    state.ecs.register::<Wallet>();
+    state.ecs.register::<Price>();
+    state.ecs.register::<Shopkeeper>();
+    state.ecs.register::<WantsToBuy>();
+    state.ecs.register::<WantsToSell>();
+    state.ecs.register::<Container>();
+    state.ecs.register::<InContainer>();
+    state.ecs.register::<WantsToTakeOut>();
+    state.ecs.register::<WantsToPutIn>();
+    state.ecs.register::<Door>();
+    state.ecs.register::<BlocksVisibility>();
+    state.ecs.register::<WantsToOpenDoor>();
+    state.ecs.register::<WantsToCloseDoor>();
+    state.ecs.register::<Trap>();
+    state.ecs.register::<Hidden>();
+    state.ecs.register::<WantsToSearch>();
+    state.ecs.register::<LightSource>();
+    state.ecs.register::<Flash>();
+    state.ecs.register::<Weight>();
+    state.ecs.register::<Equipped>();
     state.ecs.register::<Consumable>();
     state.ecs.register::<ProvidesHealing>();
     state.ecs.register::<Ranged>();
     state.ecs.register::<InflictsDamage>();
     state.ecs.register::<AreaOfEffect>();
     state.ecs.register::<Confusion>();
+    state.ecs.register::<SummonsAlly>();
+    state.ecs.register::<Charms>();
+    state.ecs.register::<Identifies>();
     state.ecs.register::<WantsToTakeTurn>();
     state.ecs.register::<TakesTurn>();
     state.ecs.register::<GlobalTurn>();
@@ -1,15 +1,70 @@
 use std::cmp::{max, min};
+use std::collections::VecDeque;
 
-use rltk::{Algorithm2D, BaseMap, Point, Rect};
+use rltk::{Algorithm2D, BaseMap, DiceType, Point, Rect, RGB};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-use crate::RNG;
+use crate::{DamageKind, RNG};
+
+/// Tiles with less light than this cannot be seen, even in view.
+const MIN_VISIBLE_LIGHT: f32 = 0.1;
+const LAVA_DAMAGE: DiceType = DiceType { n_dice: 2, die_type: 6, bonus: 0 };
+/// Pathfinding counts a hazardous tile or a known trap as this many extra steps, so monsters only cross one when there is no other way.
+const HAZARD_PATH_PENALTY: f32 = 10.0;
 
 #[derive(PartialEq, Serialize, Deserialize, Copy, Clone, Debug)]
 pub enum TileType {
     Wall,
     Floor,
+    ShallowWater,
+    DeepWater,
+    Lava,
+    Rubble,
+    Grass,
+    Bridge,
+}
+
+impl TileType {
+    pub fn is_walkable(&self) -> bool {
+        !matches!(self, TileType::Wall | TileType::DeepWater)
+    }
+
+    /// Whether the tile blocks line of sight. Grass grows tall enough to hide what is behind it.
+    pub fn is_opaque(&self) -> bool {
+        matches!(self, TileType::Wall | TileType::Grass)
+    }
+
+    /// How many steps' worth of time it takes to walk onto the tile.
+    pub fn movement_cost(&self) -> f32 {
+        match self {
+            TileType::ShallowWater |
+            TileType::Rubble => 2.0,
+            TileType::Grass => 1.5,
+            _ => 1.0,
+        }
+    }
+
+    /// What pathfinding pays to step onto the tile.
+    pub fn path_cost(&self) -> f32 {
+        if self.is_hazardous() {
+            self.movement_cost() + HAZARD_PATH_PENALTY
+        } else {
+            self.movement_cost()
+        }
+    }
+
+    /// The damage dealt to anything that steps onto the tile.
+    pub fn damage_on_enter(&self) -> Option<(DiceType, DamageKind)> {
+        match self {
+            TileType::Lava => Some((LAVA_DAMAGE, DamageKind::Fire)),
+            _ => None,
+        }
+    }
+
+    pub fn is_hazardous(&self) -> bool {
+        self.damage_on_enter().is_some()
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
@@ -21,6 +76,10 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
+    pub view_blocked: Vec<bool>,
+    pub known_traps: Vec<bool>,
+    pub light: Vec<RGB>,
+    pub doors: Vec<Point>,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -85,6 +144,64 @@ impl Map {
         self.revealed_tiles[idx]
     }
 
+    pub fn is_walkable_idx(&self, idx: usize) -> bool {
+        self.tiles[idx].is_walkable()
+    }
+
+    /// Picks a random safe, walkable tile with nothing blocking it.
+    pub fn random_free_tile(&self) -> Option<Point> {
+        let free_tiles = (0..self.count())
+            .filter(|idx| self.is_walkable_idx(*idx) && !self.tiles[*idx].is_hazardous() && !self.blocked[*idx])
+            .collect::<Vec<usize>>();
+
+        if free_tiles.is_empty() {
+            return None;
+        }
+
+        Some(self.index_to_point2d(free_tiles[RNG.range(0, free_tiles.len() as i32) as usize]))
+    }
+
+    /// Walks outwards from `start` over walkable tiles, ignoring anything standing on them,
+    /// and returns every reached tile index with its step distance.
+    pub fn walkable_distances(&self, start: Point, max_distance: i32) -> Vec<(usize, i32)> {
+        let mut distances = vec![-1; self.count()];
+        let mut reached = Vec::new();
+        let mut open = VecDeque::new();
+
+        if !self.is_valid(start.x, start.y) {
+            return reached;
+        }
+
+        let start_idx = self.point2d_to_index(start);
+        distances[start_idx] = 0;
+        open.push_back(start_idx);
+
+        while let Some(idx) = open.pop_front() {
+            let distance = distances[idx];
+            reached.push((idx, distance));
+
+            if distance >= max_distance {
+                continue;
+            }
+
+            let pt = self.index_to_point2d(idx);
+            for (delta_x, delta_y) in [(0, -1), (0, 1), (1, 0), (-1, 0)].iter() {
+                let (x, y) = (pt.x + delta_x, pt.y + delta_y);
+                if !self.is_valid(x, y) {
+                    continue;
+                }
+
+                let next_idx = self.xy_idx(x, y);
+                if distances[next_idx] < 0 && self.is_walkable_idx(next_idx) {
+                    distances[next_idx] = distance + 1;
+                    open.push_back(next_idx);
+                }
+            }
+        }
+
+        reached
+    }
+
     pub fn is_blocked(&self, x: i32, y: i32) -> bool {
         let idx = self.xy_idx(x, y);
         let is_blocked = self.blocked[idx];
@@ -104,10 +221,48 @@ impl Map {
 
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::Wall;
+            self.blocked[i] = !tile.is_walkable();
+        }
+    }
+
+    pub fn clear_view_blocked(&mut self) {
+        for view_blocked in self.view_blocked.iter_mut() {
+            *view_blocked = false;
         }
     }
 
+    pub fn clear_known_traps(&mut self) {
+        for known_trap in self.known_traps.iter_mut() {
+            *known_trap = false;
+        }
+    }
+
+    /// Whether stepping onto the tile would hurt, either from the terrain or from a trap that has been found.
+    pub fn is_hazardous_idx(&self, idx: usize) -> bool {
+        self.tiles[idx].is_hazardous() || self.known_traps[idx]
+    }
+
+    /// What pathfinding pays to step onto the tile, see `TileType::path_cost`.
+    pub fn path_cost_idx(&self, idx: usize) -> f32 {
+        if self.known_traps[idx] {
+            self.tiles[idx].path_cost() + HAZARD_PATH_PENALTY
+        } else {
+            self.tiles[idx].path_cost()
+        }
+    }
+
+    pub fn clear_light(&mut self) {
+        for light in self.light.iter_mut() {
+            *light = RGB::named(rltk::BLACK);
+        }
+    }
+
+    pub fn is_lit_idx(&self, idx: usize) -> bool {
+        let light = self.light[idx];
+
+        light.r.max(light.g).max(light.b) >= MIN_VISIBLE_LIGHT
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
@@ -124,6 +279,10 @@ impl Map {
             revealed_tiles: vec![false; map_count],
             visible_tiles: vec![false; map_count],
             blocked: vec![false; map_count],
+            view_blocked: vec![false; map_count],
+            known_traps: vec![false; map_count],
+            light: vec![RGB::named(rltk::BLACK); map_count],
+            doors: Vec::new(),
             tile_content: vec![Vec::new(); map_count],
         }
     }
@@ -148,7 +307,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        self.tiles[idx].is_opaque() || self.view_blocked[idx]
     }
 
     fn get_available_exits(&self, idx: usize) -> Vec<(usize, f32)> {
@@ -177,7 +336,7 @@ impl BaseMap for Map {
             let new_y = pt.y + *delta_y;
             if self.is_exit_valid(new_x, new_y) {
                 let idx = self.xy_idx(new_x, new_y);
-                available_exits.push((idx, *delta_cost));
+                available_exits.push((idx, *delta_cost * self.path_cost_idx(idx)));
             }
         }
 
@@ -234,6 +393,12 @@ pub fn new_map_rooms_and_corridors(width: i32, height: i32) -> Map {
         }
     }
 
+    for room in map.rooms.clone().iter().skip(1) {
+        apply_terrain_to_room(&mut map, room);
+    }
+
+    map.doors = find_doorways(&map);
+
     fn apply_room_to_map(map: &mut Map, room: &Rect) {
         for y in room.y1 + 1..=room.y2 {
             for x in room.x1 + 1..=room.x2 {
@@ -254,5 +419,98 @@ pub fn new_map_rooms_and_corridors(width: i32, height: i32) -> Map {
         }
     }
 
+    /// Dresses up a room with one terrain feature, keeping every part of it reachable
+    /// and its center safe to stand on.
+    fn apply_terrain_to_room(map: &mut Map, room: &Rect) {
+        let center = room.center();
+
+        match RNG.roll_die(6) {
+            1 => scatter_in_room(map, room, TileType::Grass, 3),
+            2 => scatter_in_room(map, room, TileType::Rubble, 4),
+            3 => scatter_in_room(map, room, TileType::ShallowWater, 3),
+            4 => {
+                // A pool of lava in a corner, away from the center.
+                for y in room.y1 + 1..=room.y1 + 2 {
+                    for x in room.x1 + 1..=room.x1 + 2 {
+                        map.set(x, y, TileType::Lava);
+                    }
+                }
+            }
+            5 => {
+                // A river running across the room, with a bridge where the corridors meet in the middle.
+                let river_x = center.x + 2;
+                for y in room.y1 + 1..=room.y2 {
+                    map.set(river_x - 1, y, TileType::ShallowWater);
+                    map.set(river_x, y, TileType::DeepWater);
+                    map.set(river_x + 1, y, TileType::ShallowWater);
+                }
+                map.set(river_x, center.y, TileType::Bridge);
+            }
+            _ => {}
+        }
+
+        bridge_entrances(map, room);
+    }
+
+    /// Bridges any deep water or lava that a corridor runs straight into, so no way in is cut off.
+    fn bridge_entrances(map: &mut Map, room: &Rect) {
+        let is_entrance = |map: &Map, x: i32, y: i32| {
+            let is_in_wall = x == room.x1 || x == room.x2 + 1 || y == room.y1 || y == room.y2 + 1;
+            is_in_wall && map.get(x, y).is_walkable()
+        };
+
+        for y in room.y1 + 1..=room.y2 {
+            for x in room.x1 + 1..=room.x2 {
+                let tile = map.get(x, y);
+                if tile.is_walkable() && !tile.is_hazardous() {
+                    continue;
+                }
+
+                let is_at_entrance = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .any(|(x, y)| is_entrance(map, *x, *y));
+
+                if is_at_entrance {
+                    map.set(x, y, TileType::Bridge);
+                }
+            }
+        }
+    }
+
+    /// Turns about one in `rarity` of the room's floor into `tile`.
+    fn scatter_in_room(map: &mut Map, room: &Rect, tile: TileType, rarity: i32) {
+        for y in room.y1 + 1..=room.y2 {
+            for x in room.x1 + 1..=room.x2 {
+                if map.get(x, y) == TileType::Floor && RNG.roll_die(rarity) == 1 {
+                    map.set(x, y, tile);
+                }
+            }
+        }
+    }
+
+    /// Finds the gaps a corridor cuts through a room's wall: floor tiles in the wall with wall on either side.
+    fn find_doorways(map: &Map) -> Vec<Point> {
+        let mut doorways: Vec<Point> = Vec::new();
+
+        let is_wall = |x: i32, y: i32| map.safe_get(x, y).is_none_or(|tile| tile == TileType::Wall);
+
+        for room in map.rooms.iter() {
+            let horizontal_walls = (room.x1 + 1..=room.x2).flat_map(|x| [Point::new(x, room.y1), Point::new(x, room.y2 + 1)]);
+            let vertical_walls = (room.y1 + 1..=room.y2).flat_map(|y| [Point::new(room.x1, y), Point::new(room.x2 + 1, y)]);
+
+            let candidates = horizontal_walls
+                .filter(|pt| is_wall(pt.x - 1, pt.y) && is_wall(pt.x + 1, pt.y))
+                .chain(vertical_walls.filter(|pt| is_wall(pt.x, pt.y - 1) && is_wall(pt.x, pt.y + 1)));
+
+            for candidate in candidates {
+                if map.get(candidate.x, candidate.y) == TileType::Floor && !doorways.contains(&candidate) {
+                    doorways.push(candidate);
+                }
+            }
+        }
+
+        doorways
+    }
+
     map
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::io::Write;
+
+use specs::prelude::*;
+
+use crate::{GameLog, GlobalTurnTimeScore, InBackpack, Kills, Name, SlainBy, Wallet};
+
+const MORGUE_FILE_PATH: &str = "./morgue.txt";
+const MORGUE_LOG_ENTRIES: usize = 10;
+
+/// Writes a summary of the player's death: who killed them, what they had slain and carried, and their last messages.
+/// Failing to write it only costs the summary, so the game still ends normally.
+pub fn write_morgue_file(ecs: &World, slain_by: &SlainBy) {
+    let contents = describe_death(ecs, slain_by);
+
+    let result = File::create(MORGUE_FILE_PATH).and_then(|mut writer| writer.write_all(contents.as_bytes()));
+    if let Err(error) = result {
+        ecs.write_resource::<GameLog>().add(format!("Unable to write {}: {}", MORGUE_FILE_PATH, error));
+    }
+}
+
+fn describe_death(ecs: &World, slain_by: &SlainBy) -> String {
+    let player_entity = *ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let kills = ecs.read_storage::<Kills>();
+    let in_backpacks = ecs.read_storage::<InBackpack>();
+    let wallets = ecs.read_storage::<Wallet>();
+    let game_log = ecs.fetch::<GameLog>();
+    let time_score = ecs.fetch::<GlobalTurnTimeScore>().time_score;
+
+    let killer_name = slain_by.killer
+        .and_then(|killer| names.get(killer))
+        .map(|name| name.name.as_str())
+        .or(slain_by.cause)
+        .unwrap_or("something unseen");
+
+    let mut lines = vec![
+        format!("{} was slain by {} at time {}.", names.get(player_entity).map_or("Player", |name| &name.name), killer_name, time_score),
+        String::new(),
+    ];
+
+    let victims = kills.get(player_entity).map_or(Vec::new(), |kills| kills.victims.clone());
+    lines.push(format!("Kills ({}):", victims.len()));
+    lines.extend(victims.iter().map(|victim| format!("  {}", victim)));
+    lines.push(String::new());
+
+    lines.push(format!("Gold: {}", wallets.get(player_entity).map_or(0, |wallet| wallet.gold)));
+    lines.push(String::new());
+
+    lines.push("Inventory:".to_string());
+    for (name, in_backpack) in (&names, &in_backpacks).join() {
+        if in_backpack.owner == player_entity {
+            lines.push(format!("  {}", name.name));
+        }
+    }
+    lines.push(String::new());
+
+    lines.push("Last messages:".to_string());
+    lines.extend(game_log.entries
+        .iter()
+        .take(MORGUE_LOG_ENTRIES)
+        .rev()
+        .map(|entry| format!("  {}", entry.get_formatted_message())));
+
+    lines.join("\n")
+}
@@ -1,29 +1,40 @@
 use rltk::{Point, VirtualKeyCode};
 use specs::prelude::*;
 
-use crate::{console_log, Context, GameLog, Item, Map, RunState, WaitCause, WantsToMelee, WantsToMove, WantsToPickUp, WantsToWait};
+use crate::{Ammunition, Confusion, console_log, Container, Context, Door, Equipped, Faction, GameLog, get_reaction, InBackpack, is_hostile, Item, Map, RangedWeapon, Reaction, Resting, RNG, RunState, Shopkeeper, TargetingPurpose, Viewshed, WaitCause, WantsToCloseDoor, WantsToMelee, WantsToMove, WantsToOpenDoor, WantsToPickUp, WantsToSearch, WantsToWait};
 
 use super::{CombatStats, Player, Position, State};
 
 pub fn player_input(state: &mut State, context: &mut Context) -> RunState {
+    let is_resting = state.ecs.read_storage::<Resting>().get(*state.ecs.fetch::<Entity>()).is_some();
+    if is_resting {
+        return continue_resting(&mut state.ecs, context.rltk.key.is_some());
+    }
+
     match context.rltk.key {
         None => { return RunState::AwaitingInput; }
         Some(key) => match key {
             VirtualKeyCode::Left |
-            VirtualKeyCode::H => try_move_player(-1, 0, &mut state.ecs),
+            VirtualKeyCode::H => return try_move_player(-1, 0, &mut state.ecs),
 
             VirtualKeyCode::Right |
-            VirtualKeyCode::L => try_move_player(1, 0, &mut state.ecs),
+            VirtualKeyCode::L => return try_move_player(1, 0, &mut state.ecs),
 
             VirtualKeyCode::Up |
-            VirtualKeyCode::J => try_move_player(0, -1, &mut state.ecs),
+            VirtualKeyCode::J => return try_move_player(0, -1, &mut state.ecs),
 
             VirtualKeyCode::Down |
-            VirtualKeyCode::K => try_move_player(0, 1, &mut state.ecs),
+            VirtualKeyCode::K => return try_move_player(0, 1, &mut state.ecs),
             VirtualKeyCode::G => get_item(&mut state.ecs),
+            VirtualKeyCode::O => return open_container(&mut state.ecs),
+            VirtualKeyCode::C => return try_close_door(&mut state.ecs),
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
+            VirtualKeyCode::T => return RunState::ShowThrowItem,
+            VirtualKeyCode::F => return try_fire(&mut state.ecs),
             VirtualKeyCode::W => wait(&mut state.ecs),
+            VirtualKeyCode::S => search(&mut state.ecs),
+            VirtualKeyCode::R => return start_resting(&mut state.ecs),
             VirtualKeyCode::PageUp => {
                 try_scroll_game_log(&mut state.ecs, 1);
                 return RunState::AwaitingInput;
@@ -50,16 +61,109 @@ pub fn wait(ecs: &mut World) {
     }
 }
 
-pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+fn search(ecs: &mut World) {
+    let player_entity = *ecs.fetch::<Entity>();
+
+    ecs.write_storage::<WantsToSearch>()
+        .insert(player_entity, WantsToSearch)
+        .expect("Unable to insert intent");
+}
+
+fn start_resting(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+
+    let is_healed = ecs.read_storage::<CombatStats>().get(player_entity).is_none_or(|stats| stats.hp >= stats.max_hp);
+    if is_healed {
+        ecs.fetch_mut::<GameLog>().add("You are already at full health.".to_string());
+        return RunState::AwaitingInput;
+    }
+
+    if is_hostile_in_view(ecs, player_entity) {
+        ecs.fetch_mut::<GameLog>().add("You cannot rest with enemies in view.".to_string());
+        return RunState::AwaitingInput;
+    }
+
+    ecs.write_storage::<Resting>().insert(player_entity, Resting).expect("Unable to insert resting");
+    ecs.fetch_mut::<GameLog>().add("You begin to rest.".to_string());
+    rest(ecs, player_entity);
+
+    RunState::PlayerTurn
+}
+
+/// Keeps waiting turn after turn until healed, a hostile shows up or a key is pressed.
+fn continue_resting(ecs: &mut World, is_key_pressed: bool) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+
+    let is_healed = ecs.read_storage::<CombatStats>().get(player_entity).is_none_or(|stats| stats.hp >= stats.max_hp);
+
+    let stop_reason = if is_key_pressed {
+        Some("You stop resting.")
+    } else if is_healed {
+        Some("You feel rested.")
+    } else if is_hostile_in_view(ecs, player_entity) {
+        Some("You stop resting, an enemy comes into view!")
+    } else {
+        None
+    };
+
+    match stop_reason {
+        Some(stop_reason) => {
+            ecs.write_storage::<Resting>().remove(player_entity);
+            ecs.fetch_mut::<GameLog>().add(stop_reason.to_string());
+            RunState::AwaitingInput
+        }
+        None => {
+            rest(ecs, player_entity);
+            RunState::PlayerTurn
+        }
+    }
+}
+
+fn rest(ecs: &mut World, player_entity: Entity) {
+    ecs.write_storage::<WantsToWait>()
+        .insert(player_entity, WantsToWait { cause: WaitCause::Rest })
+        .expect("Unable to insert intent");
+}
+
+fn is_hostile_in_view(ecs: &World, player_entity: Entity) -> bool {
+    let map = ecs.fetch::<Map>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let factions = ecs.read_storage::<Faction>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+
+    let (viewshed, own_faction) = match (viewsheds.get(player_entity), factions.get(player_entity)) {
+        (Some(viewshed), Some(own_faction)) => (viewshed, own_faction),
+        _ => return false,
+    };
+
+    viewshed.visible_tiles
+        .iter()
+        .flat_map(|tile| map.tile_content[map.xy_idx(tile.x, tile.y)].iter())
+        .filter(|other| combat_stats.get(**other).is_some_and(|stats| stats.hp > 0))
+        .any(|other| factions.get(*other).is_some_and(|faction| is_hostile(own_faction.kind, faction.kind)))
+}
+
+/// Moves, attacks, swaps places with a friend, opens a door or opens trade with a shopkeeper, depending on what is in the way.
+pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
+    let is_confused = ecs.read_storage::<Confusion>().contains(*ecs.fetch::<Entity>());
+    let (delta_x, delta_y) = if is_confused { random_direction() } else { (delta_x, delta_y) };
+
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
+    let shopkeepers = ecs.read_storage::<Shopkeeper>();
+    let doors = ecs.read_storage::<Door>();
 
+    let mut wants_to_open_door = ecs.write_storage::<WantsToOpenDoor>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
     let mut wants_to_move = ecs.write_storage::<WantsToMove>();
     let combat_stats = ecs.read_storage::<CombatStats>();
+    let factions = ecs.read_storage::<Faction>();
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
     let entities = ecs.entities();
 
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
+
+    let mut swap: Option<(Entity, Point)> = None;
 
     for (entity, _player, pos) in (&entities, &players, &mut positions).join() {
         let new_x = pos.x + delta_x;
@@ -68,20 +172,41 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
 
         if !map.is_valid_idx(new_idx) {
             console_log(format!("({}, {}) is not valid", new_x, new_y));
-            return;
+            return RunState::AwaitingInput;
         }
 
         let potential_targets = &map.tile_content[new_idx];
 
         for potential_target in potential_targets.iter() {
             let potential_target = *potential_target;
+
+            if doors.get(potential_target).is_some_and(|door| !door.open) {
+                wants_to_open_door
+                    .insert(entity, WantsToOpenDoor { door: potential_target })
+                    .expect("Unable to insert intent");
+                return RunState::PlayerTurn;
+            }
+
             let target_or_none = combat_stats.get(potential_target);
             let is_target = target_or_none.is_some();
             if is_target {
-                wants_to_melee
-                    .insert(entity, WantsToMelee { target: potential_target })
-                    .expect("Unable to insert intent");
-                return;
+                if shopkeepers.get(potential_target).is_some() {
+                    return RunState::ShowBuyItem { shopkeeper: potential_target };
+                }
+
+                let is_friendly = match (factions.get(entity), factions.get(potential_target)) {
+                    (Some(own_faction), Some(target_faction)) => get_reaction(own_faction.kind, target_faction.kind) == Reaction::Friendly,
+                    _ => false,
+                };
+
+                if is_friendly {
+                    swap = Some((potential_target, Point::new(pos.x, pos.y)));
+                } else {
+                    wants_to_melee
+                        .insert(entity, WantsToMelee { target: potential_target })
+                        .expect("Unable to insert intent");
+                    return RunState::PlayerTurn;
+                }
             }
         }
 
@@ -89,8 +214,123 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             .insert(entity, WantsToMove { destination: Point::new(new_x, new_y) })
             .expect("Unable to insert intent");
     }
+
+    if let Some((friend, player_position)) = swap {
+        if let Some(friend_position) = positions.get_mut(friend) {
+            let friend_idx = map.xy_idx(friend_position.x, friend_position.y);
+            let player_idx = map.xy_idx(player_position.x, player_position.y);
+
+            friend_position.x = player_position.x;
+            friend_position.y = player_position.y;
+
+            map.blocked[friend_idx] = false;
+            map.blocked[player_idx] = true;
+        }
+
+        if let Some(viewshed) = viewsheds.get_mut(friend) {
+            viewshed.dirty = true;
+        }
+    }
+
+    RunState::PlayerTurn
+}
+
+/// A confused player staggers in a random cardinal direction.
+fn random_direction() -> (i32, i32) {
+    match RNG.roll_die(4) {
+        1 => (1, 0),
+        2 => (-1, 0),
+        3 => (0, 1),
+        _ => (0, -1),
+    }
 }
 
+fn try_fire(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let in_backpacks = ecs.read_storage::<InBackpack>();
+    let mut game_log = ecs.fetch_mut::<GameLog>();
+
+    let weapon = (&entities, &ranged_weapons, &equipped)
+        .join()
+        .find(|(_, _, equipped)| equipped.owner == *player_entity);
+
+    let (weapon_entity, weapon) = match weapon {
+        None => {
+            game_log.add("You have no ranged weapon equipped.".to_string());
+            return RunState::AwaitingInput;
+        }
+        Some((weapon_entity, weapon, _)) => (weapon_entity, weapon),
+    };
+
+    let has_ammunition = (&ammunition, &in_backpacks)
+        .join()
+        .any(|(ammo, in_backpack)| in_backpack.owner == *player_entity && ammo.kind == weapon.ammo);
+
+    if !has_ammunition {
+        game_log.add(format!("You have no {} to fire.", weapon.ammo));
+        return RunState::AwaitingInput;
+    }
+
+    RunState::ShowTargeting {
+        range: weapon.range,
+        item: weapon_entity,
+        radius: None,
+        purpose: TargetingPurpose::Fire,
+    }
+}
+
+/// Opens a chest, or a bag lying on the floor, at the player's feet.
+fn open_container(ecs: &mut World) -> RunState {
+    let player_position = ecs.fetch::<Point>();
+    let entities = ecs.entities();
+    let containers = ecs.read_storage::<Container>();
+    let positions = ecs.read_storage::<Position>();
+
+    let container = (&entities, &containers, &positions)
+        .join()
+        .find(|(_, _, position)| position.x == player_position.x && position.y == player_position.y)
+        .map(|(container, _, _)| container);
+
+    match container {
+        Some(container) => RunState::ShowContainer { container },
+        None => {
+            ecs.fetch_mut::<GameLog>().add("There is nothing here to open.".to_string());
+            RunState::AwaitingInput
+        }
+    }
+}
+
+/// Closes an open door next to the player.
+fn try_close_door(ecs: &mut World) -> RunState {
+    let player_position = *ecs.fetch::<Point>();
+    let player_entity = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let doors = ecs.read_storage::<Door>();
+    let positions = ecs.read_storage::<Position>();
+
+    let door = (&entities, &doors, &positions)
+        .join()
+        .filter(|(_, door, _)| door.open)
+        .find(|(_, _, position)| (position.x - player_position.x).abs() + (position.y - player_position.y).abs() == 1)
+        .map(|(door, _, _)| door);
+
+    match door {
+        Some(door) => {
+            ecs.write_storage::<WantsToCloseDoor>()
+                .insert(player_entity, WantsToCloseDoor { door })
+                .expect("Unable to insert intent");
+            RunState::PlayerTurn
+        }
+        None => {
+            ecs.fetch_mut::<GameLog>().add("There is no open door next to you.".to_string());
+            RunState::AwaitingInput
+        }
+    }
+}
 
 fn try_scroll_game_log(ecs: &mut World, delta: i32) {
     let mut game_log = ecs.write_resource::<GameLog>();
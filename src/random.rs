@@ -1,6 +1,6 @@
 use std::sync::Mutex;
 
-use rltk::RandomNumberGenerator;
+use rltk::{DiceType, RandomNumberGenerator};
 
 use crate::console_log;
 
@@ -57,4 +57,16 @@ impl Random {
     pub fn roll_die(&self, die_type: i32) -> i32 {
         self.rng.lock().unwrap().roll_dice(1, die_type)
     }
+
+    pub fn roll(&self, dice: DiceType) -> i32 {
+        self.rng.lock().unwrap().roll(dice)
+    }
+}
+
+/// Parses dice notation such as "1d6+2", panicking on malformed strings since they are only written in code.
+pub fn parse_dice(dice: &str) -> DiceType {
+    match rltk::parse_dice_string(dice) {
+        Ok(dice_type) => dice_type,
+        Err(_) => panic!("Invalid dice string: {}", dice),
+    }
 }
\ No newline at end of file
@@ -7,7 +7,7 @@ use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator};
 
-use crate::{Map, SerializeMe};
+use crate::{MagicItemNames, Map, SerializeMe};
 use crate::components::*;
 
 const SAVE_FILE_PATH: &str = "./save_game.json";
@@ -28,9 +28,10 @@ macro_rules! serialize_individually {
 
 pub fn save_game(ecs: &mut World) {
     let map_copy = ecs.get_mut::<Map>().unwrap().clone();
+    let magic_item_names = ecs.get_mut::<MagicItemNames>().unwrap().clone();
     let save_helper = ecs
         .create_entity()
-        .with(SerializationHelper { map: map_copy })
+        .with(SerializationHelper { map: map_copy, magic_item_names })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -47,22 +48,78 @@ pub fn save_game(ecs: &mut World) {
             Player,
             Viewshed,
             Monster,
+            Faction,
+            Ally,
+            PackMember,
+            Fleeing,
+            Hearing,
+            Asleep,
+            Investigating,
             Name,
             BlocksTile,
             CombatStats,
+            LootTable,
             WantsToMelee,
-            SuffersDamage,
+            Kills,
+            Resistances,
+            Vulnerabilities,
             Item,
             InBackpack,
             WantsToPickUp,
             WantsToUseItem,
             WantsToDrop,
+            WantsToThrow,
+            ThrowDamage,
+            Knockback,
+            Resting,
+            HungerClock,
+            ProvidesFood,
+            WantsToBePushed,
+            WantsToFire,
+            RangedWeapon,
+            Ammunition,
+            Stackable,
+            Charges,
+            Recharges,
+            Enchantment,
+            Cursed,
+            ModifiersKnown,
+            RemovesCurse,
+            Telepathy,
+            MagicMapping,
+            Teleports,
+            DetectsMonsters,
+            WantsToTeleport,
+            Gold,
+            Wallet,
+            Price,
+            Shopkeeper,
+            WantsToBuy,
+            WantsToSell,
+            Container,
+            InContainer,
+            WantsToTakeOut,
+            WantsToPutIn,
+            Door,
+            BlocksVisibility,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            Trap,
+            Hidden,
+            WantsToSearch,
+            LightSource,
+            Flash,
+            Weight,
+            Equipped,
             Consumable,
             ProvidesHealing,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
             Confusion,
+            SummonsAlly,
+            Charms,
+            Identifies,
             WantsToTakeTurn,
             TakesTurn,
             GlobalTurn,
@@ -126,22 +183,78 @@ pub fn load_game(ecs: &mut World) {
             Player,
             Viewshed,
             Monster,
+            Faction,
+            Ally,
+            PackMember,
+            Fleeing,
+            Hearing,
+            Asleep,
+            Investigating,
             Name,
             BlocksTile,
             CombatStats,
+            LootTable,
             WantsToMelee,
-            SuffersDamage,
+            Kills,
+            Resistances,
+            Vulnerabilities,
             Item,
             InBackpack,
             WantsToPickUp,
             WantsToUseItem,
             WantsToDrop,
+            WantsToThrow,
+            ThrowDamage,
+            Knockback,
+            Resting,
+            HungerClock,
+            ProvidesFood,
+            WantsToBePushed,
+            WantsToFire,
+            RangedWeapon,
+            Ammunition,
+            Stackable,
+            Charges,
+            Recharges,
+            Enchantment,
+            Cursed,
+            ModifiersKnown,
+            RemovesCurse,
+            Telepathy,
+            MagicMapping,
+            Teleports,
+            DetectsMonsters,
+            WantsToTeleport,
+            Gold,
+            Wallet,
+            Price,
+            Shopkeeper,
+            WantsToBuy,
+            WantsToSell,
+            Container,
+            InContainer,
+            WantsToTakeOut,
+            WantsToPutIn,
+            Door,
+            BlocksVisibility,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            Trap,
+            Hidden,
+            WantsToSearch,
+            LightSource,
+            Flash,
+            Weight,
+            Equipped,
             Consumable,
             ProvidesHealing,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
             Confusion,
+            SummonsAlly,
+            Charms,
+            Identifies,
             WantsToTakeTurn,
             TakesTurn,
             GlobalTurn,
@@ -169,6 +282,7 @@ pub fn load_game(ecs: &mut World) {
             let mut world_map = ecs.write_resource::<Map>();
             *world_map = helper.map.clone();
             world_map.tile_content = vec![Vec::new(); world_map.count()];
+            *ecs.write_resource::<MagicItemNames>() = helper.magic_item_names.clone();
             delete_me = Some(entity);
         }
 
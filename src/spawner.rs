@@ -3,11 +3,27 @@ use specs::{Entity, World};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
-use crate::{AreaOfEffect, BlocksTile, CanMelee, CanMove, CombatStats, Confusion, Consumable, DEBUG, GlobalTurn, GlobalTurnTimeScore, InBackpack, InflictsDamage, Item, Map, Monster, Name, Player, Position, ProvidesHealing, Ranged, Renderable, RNG, SerializeMe, TakesTurn, Viewshed};
+use crate::{Ally, AmmoKind, Ammunition, AreaOfEffect, Asleep, BlocksTile, BlocksVisibility, CanMelee, CanMove, Charges, Charms, CombatStats, Confusion, Consumable, CLOSED_DOOR_GLYPH, Container, Cursed, DamageKind, DEBUG, DetectsMonsters, Door, Enchantment, Faction, FactionKind, Flash, GameLog, Gold, GlobalTurn, GlobalTurnTimeScore, Hearing, Hidden, HUNGER_STATE_DURATION, HungerClock, HungerState, Identifies, InBackpack, InContainer, InflictsDamage, Item, Kills, Knockback, LightSource, LootEntry, LootTable, MagicItemKind, MagicItemNames, MagicMapping, Map, ModifiersKnown, Monster, Name, PackMember, parse_dice, Player, Position, Price, ProvidesFood, ProvidesHealing, Ranged, RangedWeapon, Recharges, RemovesCurse, Renderable, Resistances, RNG, SerializeMe, Shopkeeper, Stackable, SummonsAlly, TakesTurn, Teleports, ThrowDamage, Trap, TrapKind, Viewshed, Vulnerabilities, Wallet, Weight};
 
 const MAX_MONSTERS: i32 = 4;
 const MAX_ITEMS: i32 = 2;
 const DROP_OFFSET: i32 = 3;
+const WAR_PARTY_CHANCE: i32 = 4;
+const MIN_WAR_PARTY_FOLLOWERS: i32 = 2;
+const MAX_WAR_PARTY_FOLLOWERS: i32 = 3;
+const AMMUNITION_BUNDLE: i32 = 10;
+const ITEM_MODIFIER_CHANCE: i32 = 6;
+const MAX_ENCHANTMENT_LEVEL: i32 = 2;
+const GOLD_CHANCE: i32 = 3;
+const SHOP_STOCK: i32 = 6;
+const SHOPKEEPER_GOLD: i32 = 300;
+const CHEST_CHANCE: i32 = 5;
+const TRAP_CHANCE: i32 = 3;
+const TORCH_CHANCE: i32 = 2;
+const PLAYER_LIGHT_RADIUS: i32 = 6;
+const TORCH_LIGHT_RADIUS: i32 = 5;
+const FLASH_TURNS: i32 = 1;
+const MAX_CHEST_ITEMS: i32 = 3;
 
 pub fn player(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs
@@ -20,6 +36,7 @@ pub fn player(ecs: &mut World, x: i32, y: i32) -> Entity {
             render_order: 0,
         })
         .with(Player)
+        .with(Faction { kind: FactionKind::Player })
         .with(Viewshed {
             visible_tiles: Vec::new(),
             range: 8,
@@ -31,10 +48,15 @@ pub fn player(ecs: &mut World, x: i32, y: i32) -> Entity {
             hp: 30,
             defense: 2,
             power: 5,
+            regen_rate: 8,
         })
         .with(TakesTurn { time_score: 0 })
         .with(CanMove { time_cost: 20 })
-        .with(CanMelee { time_cost: 110 })
+        .with(CanMelee { time_cost: 110, damage: parse_dice("1d6+1") })
+        .with(Kills { victims: Vec::new() })
+        .with(HungerClock { state: HungerState::WellFed, duration: HUNGER_STATE_DURATION })
+        .with(Wallet { gold: 0 })
+        .with(LightSource { radius: PLAYER_LIGHT_RADIUS, color: RGB::from_f32(1.0, 0.95, 0.8) })
         .marked::<SimpleMarker<SerializeMe>>()
         .build()
 }
@@ -42,33 +64,171 @@ pub fn player(ecs: &mut World, x: i32, y: i32) -> Entity {
 
 pub fn random_monster(ecs: &mut World, x: i32, y: i32) {
     if RNG.flip_coin() {
-        orc(ecs, x, y)
+        orc(ecs, x, y);
     } else {
-        goblin(ecs, x, y)
+        goblin(ecs, x, y);
+    }
+}
+
+pub fn goblin_war_party(ecs: &mut World, map: &Map, room: &Rect) {
+    let leader_idx = get_spawn_points(map, 1, room)[0];
+    let leader_pt = map.index_to_point2d(leader_idx);
+    let leader = goblin_warboss(ecs, leader_pt.x, leader_pt.y);
+
+    let follower_count = RNG.inclusive_range(MIN_WAR_PARTY_FOLLOWERS, MAX_WAR_PARTY_FOLLOWERS);
+    for idx in get_spawn_points_near(map, follower_count, room, leader_pt) {
+        let pt = map.index_to_point2d(idx);
+        let follower = goblin(ecs, pt.x, pt.y);
+        ecs.write_storage::<PackMember>()
+            .insert(follower, PackMember { leader })
+            .expect("Unable to insert pack member");
     }
 }
 
 static mut COUNTER: i32 = 1;
 
 pub fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc")
+    let orc = monster(ecs, x, y, MonsterTemplate {
+        glyph: rltk::to_cp437('o'),
+        name: "Orc",
+        faction: FactionKind::Orc,
+        max_hp: 16,
+        power: 4,
+        damage: "1d6",
+    });
+    add_loot_table(ecs, orc, 1, 25, &[("Health Potion", 3), ("Fireball Scroll", 1)]);
+    add_damage_affinities(ecs, orc, &[DamageKind::Poison], &[]);
+}
+
+pub fn goblin(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let goblin = monster(ecs, x, y, MonsterTemplate {
+        glyph: rltk::to_cp437('g'),
+        name: "Goblin",
+        faction: FactionKind::Goblin,
+        max_hp: 16,
+        power: 4,
+        damage: "1d4+1",
+    });
+    add_loot_table(ecs, goblin, 1, 20, &[("Magic Missile Scroll", 2), ("Confusion Scroll", 1), ("Throwing Knife", 2)]);
+    add_damage_affinities(ecs, goblin, &[], &[DamageKind::Fire]);
+
+    goblin
 }
 
-pub fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin")
+pub fn goblin_warboss(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let warboss = monster(ecs, x, y, MonsterTemplate {
+        glyph: rltk::to_cp437('G'),
+        name: "Goblin Warboss",
+        faction: FactionKind::Goblin,
+        max_hp: 24,
+        power: 6,
+        damage: "1d8+1",
+    });
+    add_loot_table(ecs, warboss, 2, 75, &[("Health Potion", 2), ("Fireball Scroll", 1), ("Summoning Scroll", 1)]);
+    add_damage_affinities(ecs, warboss, &[DamageKind::Cold], &[DamageKind::Fire]);
+    ecs.write_storage::<Knockback>()
+        .insert(warboss, Knockback { distance: 1 })
+        .expect("Unable to insert knockback");
+    ecs.write_storage::<LightSource>()
+        .insert(warboss, LightSource { radius: TORCH_LIGHT_RADIUS, color: torch_light() })
+        .expect("Unable to insert light source");
+    health_potion_in_pack(ecs, warboss);
+
+    warboss
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: u8, name: S) {
-    let monster_builder = ecs
+fn add_loot_table(ecs: &mut World, entity: Entity, rolls: i32, drop_chance: i32, entries: &[(&str, i32)]) {
+    let entries = entries
+        .iter()
+        .map(|(name, weight)| LootEntry { name: name.to_string(), weight: *weight })
+        .collect();
+
+    ecs.write_storage::<LootTable>()
+        .insert(entity, LootTable { rolls, drop_chance, entries })
+        .expect("Unable to insert loot table");
+}
+
+/// Resisted damage kinds are halved and vulnerable ones doubled, see `DamageSystem`.
+fn add_damage_affinities(ecs: &mut World, entity: Entity, resistances: &[DamageKind], vulnerabilities: &[DamageKind]) {
+    if !resistances.is_empty() {
+        ecs.write_storage::<Resistances>()
+            .insert(entity, Resistances { kinds: resistances.to_vec() })
+            .expect("Unable to insert resistances");
+    }
+
+    if !vulnerabilities.is_empty() {
+        ecs.write_storage::<Vulnerabilities>()
+            .insert(entity, Vulnerabilities { kinds: vulnerabilities.to_vec() })
+            .expect("Unable to insert vulnerabilities");
+    }
+}
+
+pub fn roll_loot(loot_table: &LootTable) -> Vec<String> {
+    let mut loot = Vec::new();
+    let total_weight: i32 = loot_table.entries.iter().map(|entry| entry.weight).sum();
+
+    if total_weight <= 0 {
+        return loot;
+    }
+
+    for _ in 0..loot_table.rolls {
+        if RNG.inclusive_range(1, 100) > loot_table.drop_chance {
+            continue;
+        }
+
+        let mut roll = RNG.range(0, total_weight);
+        for entry in loot_table.entries.iter() {
+            if roll < entry.weight {
+                loot.push(entry.name.to_string());
+                break;
+            }
+            roll -= entry.weight;
+        }
+    }
+
+    loot
+}
+
+pub fn corpse(ecs: &mut World, x: i32, y: i32, name: &str) {
+    ecs
         .create_entity()
         .with(Position { x, y })
         .with(Renderable {
-            glyph,
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::DARK_RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: format!("{} corpse", name) })
+        .with(Weight { weight: 20. })
+        .with(Item)
+        .with(Consumable)
+        .with(ProvidesFood)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+struct MonsterTemplate<'a> {
+    glyph: u8,
+    name: &'a str,
+    faction: FactionKind,
+    max_hp: i32,
+    power: i32,
+    damage: &'a str,
+}
+
+fn monster(ecs: &mut World, x: i32, y: i32, template: MonsterTemplate) -> Entity {
+    let mut monster_builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: template.glyph,
             fg: RGB::named(rltk::RED),
             bg: RGB::named(rltk::BLACK),
             render_order: 1,
         })
         .with(Monster)
+        .with(Faction { kind: template.faction })
         .with(Viewshed {
             visible_tiles: Vec::new(),
             range: 8,
@@ -76,49 +236,146 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: u8, name: S) {
         })
         .with(BlocksTile)
         .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
+            max_hp: template.max_hp,
+            hp: template.max_hp,
             defense: 1,
-            power: 4,
+            power: template.power,
+            regen_rate: 20,
         })
         .with(TakesTurn { time_score: 0 })
         .with(CanMove { time_cost: 30 })
-        .with(CanMelee { time_cost: 180 })
+        .with(CanMelee { time_cost: 180, damage: parse_dice(template.damage) })
+        .with(Hearing { radius: 8 })
         .marked::<SimpleMarker<SerializeMe>>();
 
+    if RNG.roll_die(3) == 1 {
+        monster_builder = monster_builder.with(Asleep);
+    }
+
 
     if DEBUG {
         unsafe {
-            monster_builder.with(Name { name: format!("{} #{}", name.to_string(), COUNTER) }).build();
-            COUNTER += 1
-        };
+            let entity = monster_builder.with(Name { name: format!("{} #{}", template.name, COUNTER) }).build();
+            COUNTER += 1;
+            entity
+        }
     } else {
-        monster_builder.with(Name { name: name.to_string() }).build();
+        monster_builder.with(Name { name: template.name.to_string() }).build()
     }
 }
 
+pub fn wolf(ecs: &mut World, x: i32, y: i32) {
+    ally(ecs, x, y, rltk::to_cp437('w'), "Wolf")
+}
+
+fn ally<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: u8, name: S) {
+    let time_score = ecs.fetch::<GlobalTurnTimeScore>().time_score;
+
+    ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph,
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Ally)
+        .with(Faction { kind: FactionKind::Player })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(BlocksTile)
+        .with(CombatStats {
+            max_hp: 12,
+            hp: 12,
+            defense: 1,
+            power: 4,
+            regen_rate: 10,
+        })
+        .with(TakesTurn { time_score })
+        .with(CanMove { time_cost: 20 })
+        .with(CanMelee { time_cost: 120, damage: parse_dice("1d6") })
+        .with(Name { name: name.to_string() })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 pub fn random_item(ecs: &mut World, x: i32, y: i32) {
-    match RNG.roll_die(4) {
+    match RNG.roll_die(19) {
         1 => { health_potion(ecs, x, y) }
         2 => { fireball_scroll(ecs, x, y) }
         3 => { confusion_scroll(ecs, x, y) }
+        4 => { summoning_scroll(ecs, x, y) }
+        5 => { charm_scroll(ecs, x, y) }
+        6 => { throwing_knife(ecs, x, y) }
+        7 => { if RNG.flip_coin() { shortbow(ecs, x, y) } else { crossbow(ecs, x, y) } }
+        8 => { ammunition(ecs, x, y, if RNG.flip_coin() { AmmoKind::Arrow } else { AmmoKind::Bolt }, AMMUNITION_BUNDLE) }
+        9 => { force_bolt_scroll(ecs, x, y) }
+        10 => { ration(ecs, x, y) }
+        11 => { identify_scroll(ecs, x, y) }
+        12 => { random_wand(ecs, x, y) }
+        13 => { recharging_scroll(ecs, x, y) }
+        14 => { remove_curse_scroll(ecs, x, y) }
+        15 => { magic_mapping_scroll(ecs, x, y) }
+        16 => { teleport_scroll(ecs, x, y) }
+        17 => { detect_monsters_scroll(ecs, x, y) }
+        18 => { bag(ecs, x, y) }
         _ => { magic_missile_scroll(ecs, x, y) }
     }
 }
 
+/// Spawns a single one of the named item, as listed in loot tables, returning false when the name is unknown.
+pub fn spawn_named_item(ecs: &mut World, name: &str, x: i32, y: i32) -> bool {
+    match name {
+        "Health Potion" => health_potion(ecs, x, y),
+        "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
+        "Fireball Scroll" => fireball_scroll(ecs, x, y),
+        "Confusion Scroll" => confusion_scroll(ecs, x, y),
+        "Summoning Scroll" => summoning_scroll(ecs, x, y),
+        "Charm Monster Scroll" => charm_scroll(ecs, x, y),
+        "Throwing Knife" => throwing_knife(ecs, x, y),
+        "Force Bolt Scroll" => force_bolt_scroll(ecs, x, y),
+        "Ration" => ration(ecs, x, y),
+        "Identify Scroll" => identify_scroll(ecs, x, y),
+        "Recharging Scroll" => recharging_scroll(ecs, x, y),
+        "Remove Curse Scroll" => remove_curse_scroll(ecs, x, y),
+        "Magic Mapping Scroll" => magic_mapping_scroll(ecs, x, y),
+        "Teleport Scroll" => teleport_scroll(ecs, x, y),
+        "Detect Monsters Scroll" => detect_monsters_scroll(ecs, x, y),
+        "Bag" => bag(ecs, x, y),
+        "Magic Missile Wand" => magic_missile_wand(ecs, x, y),
+        "Fireball Wand" => fireball_wand(ecs, x, y),
+        "Confusion Wand" => confusion_wand(ecs, x, y),
+        "Shortbow" => shortbow(ecs, x, y),
+        "Crossbow" => crossbow(ecs, x, y),
+        "Arrows" => ammunition(ecs, x, y, AmmoKind::Arrow, 1),
+        "Bolts" => ammunition(ecs, x, y, AmmoKind::Bolt, 1),
+        _ => return false,
+    }
+
+    true
+}
+
 pub fn health_potion(ecs: &mut World, x: i32, y: i32) {
     health_potion_base(ecs)
         .with(Position { x, y })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 pub fn health_potion_in_pack(ecs: &mut World, owner: Entity) {
     health_potion_base(ecs)
         .with(InBackpack { owner })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 pub fn health_potion_base(ecs: &mut World) -> EntityBuilder {
+    register_magic_item(ecs, "Health Potion", MagicItemKind::Potion);
+
     ecs
         .create_entity()
         .with(Renderable {
@@ -128,6 +385,8 @@ pub fn health_potion_base(ecs: &mut World) -> EntityBuilder {
             render_order: 2,
         })
         .with(Name { name: "Health Potion".to_string() })
+        .with(Weight { weight: 1. })
+        .with(Price { value: 20 })
         .with(Item)
         .with(Consumable)
         .with(ProvidesHealing {
@@ -139,10 +398,13 @@ pub fn health_potion_base(ecs: &mut World) -> EntityBuilder {
 pub fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
     magic_missile_scroll_base(ecs)
         .with(Position { x, y })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 pub fn magic_missile_scroll_base(ecs: &mut World) -> EntityBuilder {
+    register_magic_item(ecs, "Magic Missile Scroll", MagicItemKind::Scroll);
+
     ecs
         .create_entity()
         .with(Renderable {
@@ -152,32 +414,39 @@ pub fn magic_missile_scroll_base(ecs: &mut World) -> EntityBuilder {
             render_order: 2,
         })
         .with(Name { name: "Magic Missile Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 25 })
         .with(Item)
         .with(Consumable)
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 8 })
+        .with(InflictsDamage { damage: 8, kind: DamageKind::Magic })
         .marked::<SimpleMarker<SerializeMe>>()
 }
 
 pub fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
     fireball_scroll_base(ecs)
         .with(Position { x, y })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
     confusion_scroll_base(ecs)
         .with(Position { x, y })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 fn confusion_scroll_in_pack(ecs: &mut World, owner: Entity) {
     confusion_scroll_base(ecs)
         .with(InBackpack { owner })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
 fn confusion_scroll_base(ecs: &mut World) -> EntityBuilder {
+    register_magic_item(ecs, "Confusion Scroll", MagicItemKind::Scroll);
+
     ecs.create_entity()
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
@@ -186,6 +455,8 @@ fn confusion_scroll_base(ecs: &mut World) -> EntityBuilder {
             render_order: 2,
         })
         .with(Name { name: "Confusion Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 20 })
         .with(Item)
         .with(Consumable)
         .with(Ranged { range: 6 })
@@ -193,21 +464,314 @@ fn confusion_scroll_base(ecs: &mut World) -> EntityBuilder {
         .marked::<SimpleMarker<SerializeMe>>()
 }
 
+fn summoning_scroll(ecs: &mut World, x: i32, y: i32) {
+    register_magic_item(ecs, "Summoning Scroll", MagicItemKind::Scroll);
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Summoning Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 40 })
+        .with(Item)
+        .with(Consumable)
+        .with(SummonsAlly)
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn charm_scroll(ecs: &mut World, x: i32, y: i32) {
+    register_magic_item(ecs, "Charm Monster Scroll", MagicItemKind::Scroll);
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Charm Monster Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 40 })
+        .with(Item)
+        .with(Consumable)
+        .with(Ranged { range: 6 })
+        .with(Charms)
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn identify_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Identify Scroll", 20, RGB::named(rltk::WHITE))
+        .with(Identifies)
+        .build();
+}
+
+fn recharging_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Recharging Scroll", 40, RGB::named(rltk::YELLOW))
+        .with(Recharges)
+        .build();
+}
+
+fn random_wand(ecs: &mut World, x: i32, y: i32) {
+    match RNG.roll_die(3) {
+        1 => fireball_wand(ecs, x, y),
+        2 => confusion_wand(ecs, x, y),
+        _ => magic_missile_wand(ecs, x, y),
+    }
+}
+
+fn magic_missile_wand(ecs: &mut World, x: i32, y: i32) {
+    wand_base(ecs, x, y, "Magic Missile Wand", 60, 6)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 8, kind: DamageKind::Magic })
+        .build();
+}
+
+fn fireball_wand(ecs: &mut World, x: i32, y: i32) {
+    wand_base(ecs, x, y, "Fireball Wand", 100, 3)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 20, kind: DamageKind::Fire })
+        .with(AreaOfEffect { radius: 3 })
+        .build();
+}
+
+fn confusion_wand(ecs: &mut World, x: i32, y: i32) {
+    wand_base(ecs, x, y, "Confusion Wand", 50, 4)
+        .with(Ranged { range: 6 })
+        .with(Confusion { turns: 4 })
+        .build();
+}
+
+/// Wands are found partially charged, and are kept rather than consumed once empty.
+fn wand_base<'a>(ecs: &'a mut World, x: i32, y: i32, name: &str, price: i32, max_charges: i32) -> EntityBuilder<'a> {
+    register_magic_item(ecs, name, MagicItemKind::Wand);
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: name.to_string() })
+        .with(Weight { weight: 1. })
+        .with(Price { value: price })
+        .with(Item)
+        .with(Charges { current: RNG.inclusive_range(1, max_charges), max: max_charges })
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+fn remove_curse_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Remove Curse Scroll", 30, RGB::named(rltk::LIGHT_GRAY))
+        .with(RemovesCurse)
+        .build();
+}
+
+fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Magic Mapping Scroll", 35, RGB::named(rltk::LIGHT_CYAN))
+        .with(MagicMapping)
+        .build();
+}
+
+fn teleport_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Teleport Scroll", 30, RGB::named(rltk::VIOLET))
+        .with(Teleports)
+        .build();
+}
+
+fn detect_monsters_scroll(ecs: &mut World, x: i32, y: i32) {
+    utility_scroll_base(ecs, x, y, "Detect Monsters Scroll", 20, RGB::named(rltk::RED))
+        .with(DetectsMonsters { turns: 30 })
+        .build();
+}
+
+fn utility_scroll_base<'a>(ecs: &'a mut World, x: i32, y: i32, name: &str, price: i32, fg: RGB) -> EntityBuilder<'a> {
+    register_magic_item(ecs, name, MagicItemKind::Scroll);
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: name.to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: price })
+        .with(Item)
+        .with(Consumable)
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+/// Scrolls, potions and wands go by a per-run appearance until the player identifies them.
+fn register_magic_item(ecs: &mut World, name: &str, kind: MagicItemKind) {
+    ecs.write_resource::<MagicItemNames>().register(name, kind);
+}
+
+fn ration(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Ration".to_string() })
+        .with(Weight { weight: 1. })
+        .with(Price { value: 5 })
+        .with(Item)
+        .with(Consumable)
+        .with(ProvidesFood)
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn force_bolt_scroll(ecs: &mut World, x: i32, y: i32) {
+    register_magic_item(ecs, "Force Bolt Scroll", MagicItemKind::Scroll);
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Force Bolt Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 25 })
+        .with(Item)
+        .with(Consumable)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 4, kind: DamageKind::Magic })
+        .with(Knockback { distance: 3 })
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn throwing_knife(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Throwing Knife".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 5 })
+        .with(Item)
+        .with(ThrowDamage { damage: parse_dice("1d6") })
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn shortbow(ecs: &mut World, x: i32, y: i32) {
+    ranged_weapon(ecs, x, y, "Shortbow", 2., 30, RangedWeapon { range: 8, damage: parse_dice("1d6"), ammo: AmmoKind::Arrow });
+}
+
+fn crossbow(ecs: &mut World, x: i32, y: i32) {
+    ranged_weapon(ecs, x, y, "Crossbow", 6., 45, RangedWeapon { range: 6, damage: parse_dice("1d8+2"), ammo: AmmoKind::Bolt });
+}
+
+fn ranged_weapon(ecs: &mut World, x: i32, y: i32, name: &str, weight: f32, price: i32, weapon: RangedWeapon) {
+    let entity = ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('}'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: name.to_string() })
+        .with(Weight { weight })
+        .with(Price { value: price })
+        .with(Item)
+        .with(weapon)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    add_item_modifiers(ecs, entity);
+}
+
+/// Equippable items are sometimes enchanted, or cursed with a negative enchantment instead.
+fn add_item_modifiers(ecs: &mut World, item: Entity) {
+    let level = match RNG.roll_die(ITEM_MODIFIER_CHANCE) {
+        1 => {
+            ecs.write_storage::<Cursed>().insert(item, Cursed).expect("Unable to insert curse");
+            -RNG.inclusive_range(1, MAX_ENCHANTMENT_LEVEL)
+        }
+        2 => RNG.inclusive_range(1, MAX_ENCHANTMENT_LEVEL),
+        _ => return,
+    };
+
+    ecs.write_storage::<Enchantment>()
+        .insert(item, Enchantment { level })
+        .expect("Unable to insert enchantment");
+}
+
+pub fn ammunition_name(kind: AmmoKind) -> &'static str {
+    match kind {
+        AmmoKind::Arrow => "Arrows",
+        AmmoKind::Bolt => "Bolts",
+    }
+}
+
+fn ammunition(ecs: &mut World, x: i32, y: i32, kind: AmmoKind, quantity: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('|'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: ammunition_name(kind).to_string() })
+        .with(Weight { weight: 0.1 })
+        .with(Price { value: 1 })
+        .with(Item)
+        .with(Ammunition { kind })
+        .with(Stackable { quantity })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 fn magic_missile_scroll_in_pack(ecs: &mut World, owner: Entity) {
     magic_missile_scroll_base(ecs)
         .with(InBackpack { owner })
+        .with(Stackable { quantity: 1 })
         .build();
 }
 
-fn fireball_scroll_in_pack(ecs: &mut World, owner: Entity) {
+fn fireball_scroll_in_pack(ecs: &mut World, owner: Entity, quantity: i32) {
     fireball_scroll_base(ecs)
         .with(InBackpack {
             owner,
         })
+        .with(Stackable { quantity })
         .build();
 }
 
 fn fireball_scroll_base(ecs: &mut World) -> EntityBuilder {
+    register_magic_item(ecs, "Fireball Scroll", MagicItemKind::Scroll);
+
     ecs
         .create_entity()
         .with(Renderable {
@@ -217,10 +781,12 @@ fn fireball_scroll_base(ecs: &mut World) -> EntityBuilder {
             render_order: 2,
         })
         .with(Name { name: "Fireball Scroll".to_string() })
+        .with(Weight { weight: 0.5 })
+        .with(Price { value: 50 })
         .with(Item)
         .with(Consumable)
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
+        .with(InflictsDamage { damage: 20, kind: DamageKind::Fire })
         .with(AreaOfEffect { radius: 3 })
         .marked::<SimpleMarker<SerializeMe>>()
 }
@@ -235,34 +801,266 @@ pub fn spawn_map(ecs: &mut World, map: &Map) {
     confusion_scroll_in_pack(ecs, player);
     magic_missile_scroll_in_pack(ecs, player);
 
-    for _ in 0..3 {
-        fireball_scroll_in_pack(ecs, player);
-    }
+    fireball_scroll_in_pack(ecs, player, 3);
 
     ecs.insert(player);
 
-    let rooms = map.rooms.iter().skip(1);
-    for room in rooms {
-        spawn_room(ecs, map, room);
+    let shop_room = if map.rooms.len() > 1 { Some(RNG.range(1, map.rooms.len() as i32) as usize) } else { None };
+
+    for (i, room) in map.rooms.iter().enumerate().skip(1) {
+        if Some(i) == shop_room {
+            spawn_shop(ecs, room);
+        } else {
+            spawn_room(ecs, map, room);
+        }
+    }
+
+    for doorway in map.doors.iter() {
+        door(ecs, doorway.x, doorway.y);
     }
 }
 
+/// A shopkeeper in the middle of the room, carrying the stock the player can buy.
+fn spawn_shop(ecs: &mut World, room: &Rect) {
+    let center = room.center();
+
+    let shopkeeper = ecs.create_entity()
+        .with(Position { x: center.x, y: center.y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Name { name: "Shopkeeper".to_string() })
+        .with(Faction { kind: FactionKind::Merchant })
+        .with(Shopkeeper)
+        .with(BlocksTile)
+        .with(CombatStats {
+            max_hp: 40,
+            hp: 40,
+            defense: 3,
+            power: 8,
+            regen_rate: 10,
+        })
+        .with(Wallet { gold: SHOPKEEPER_GOLD })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    for _ in 0..SHOP_STOCK {
+        random_item(ecs, center.x, center.y);
+    }
+
+    let stock = items_at(ecs, center.x, center.y);
+    let mut positions = ecs.write_storage::<Position>();
+    let mut in_backpacks = ecs.write_storage::<InBackpack>();
+
+    for item in stock {
+        positions.remove(item);
+        in_backpacks.insert(item, InBackpack { owner: shopkeeper }).expect("Unable to insert backpack entry");
+    }
+}
+
+fn chest(ecs: &mut World, x: i32, y: i32) {
+    let chest = ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('■'),
+            fg: RGB::named(rltk::CHOCOLATE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: "Chest".to_string() })
+        .with(Container)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    // Other items may already lie on this tile, so only the ones spawned here go in the chest.
+    let already_here = items_at(ecs, x, y);
+    for _ in 0..RNG.inclusive_range(1, MAX_CHEST_ITEMS) {
+        random_item(ecs, x, y);
+    }
+
+    let contents: Vec<Entity> = items_at(ecs, x, y)
+        .into_iter()
+        .filter(|item| !already_here.contains(item))
+        .collect();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut in_containers = ecs.write_storage::<InContainer>();
+
+    for item in contents {
+        positions.remove(item);
+        in_containers.insert(item, InContainer { container: chest }).expect("Unable to insert container entry");
+    }
+}
+
+/// The items lying on the floor at the given tile, used to move freshly spawned items into a pack or container.
+fn items_at(ecs: &World, x: i32, y: i32) -> Vec<Entity> {
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+
+    (&entities, &items, &positions)
+        .join()
+        .filter(|(_, _, position)| position.x == x && position.y == y)
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+fn bag(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('('),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Bag".to_string() })
+        .with(Weight { weight: 1. })
+        .with(Price { value: 15 })
+        .with(Item)
+        .with(Container)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn door(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(CLOSED_DOOR_GLYPH),
+            fg: RGB::named(rltk::CHOCOLATE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: "Door".to_string() })
+        .with(Door { open: false })
+        .with(BlocksTile)
+        .with(BlocksVisibility)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn torch_light() -> RGB {
+    RGB::from_f32(1.0, 0.7, 0.4)
+}
+
+fn torch(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: "Torch".to_string() })
+        .with(LightSource { radius: TORCH_LIGHT_RADIUS, color: torch_light() })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// The brief glare of an explosion, lighting up everything around it.
+fn flash(ecs: &mut World, x: i32, y: i32, radius: i32, color: RGB) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(LightSource { radius, color })
+        .with(Flash { turns: FLASH_TURNS })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn random_trap(ecs: &mut World, x: i32, y: i32) {
+    match RNG.roll_die(4) {
+        1 => trap(ecs, x, y, "Dart Trap", TrapKind::Dart, RGB::named(rltk::GREY)),
+        2 => trap(ecs, x, y, "Teleport Trap", TrapKind::Teleport, RGB::named(rltk::CYAN)),
+        3 => trap(ecs, x, y, "Alarm Trap", TrapKind::Alarm, RGB::named(rltk::YELLOW)),
+        _ => trap(ecs, x, y, "Confusion Gas Trap", TrapKind::ConfusionGas, RGB::named(rltk::MAGENTA)),
+    }
+}
+
+fn trap(ecs: &mut World, x: i32, y: i32, name: &str, kind: TrapKind, fg: RGB) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name { name: name.to_string() })
+        .with(Trap { kind })
+        .with(Hidden)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn gold(ecs: &mut World, x: i32, y: i32, amount: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('$'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name: "Gold".to_string() })
+        .with(Item)
+        .with(Gold { amount })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 fn spawn_room(ecs: &mut World, map: &Map, room: &Rect) {
     let monster_count = RNG.inclusive_range(0, MAX_MONSTERS + DROP_OFFSET) - DROP_OFFSET;
     let item_count = RNG.inclusive_range(0, MAX_ITEMS + DROP_OFFSET) - DROP_OFFSET;
 
-    let monster_spawn_points = get_spawn_points(map, monster_count, room);
     let item_spawn_points = get_spawn_points(map, item_count, room);
 
-    for idx in monster_spawn_points {
-        let pt = map.index_to_point2d(idx);
-        random_monster(ecs, pt.x, pt.y);
+    if RNG.roll_die(WAR_PARTY_CHANCE) == 1 {
+        goblin_war_party(ecs, map, room);
+    } else {
+        let monster_spawn_points = get_spawn_points(map, monster_count, room);
+
+        for idx in monster_spawn_points {
+            let pt = map.index_to_point2d(idx);
+            random_monster(ecs, pt.x, pt.y);
+        }
     }
 
     for idx in item_spawn_points {
         let pt = map.index_to_point2d(idx);
         random_item(ecs, pt.x, pt.y);
     }
+
+    if RNG.roll_die(CHEST_CHANCE) == 1 {
+        for idx in get_spawn_points(map, 1, room) {
+            let pt = map.index_to_point2d(idx);
+            chest(ecs, pt.x, pt.y);
+        }
+    }
+
+    if RNG.roll_die(GOLD_CHANCE) == 1 {
+        for idx in get_spawn_points(map, 1, room) {
+            let pt = map.index_to_point2d(idx);
+            gold(ecs, pt.x, pt.y, RNG.roll(parse_dice("3d10")));
+        }
+    }
+
+    if RNG.roll_die(TORCH_CHANCE) == 1 {
+        for idx in get_spawn_points(map, 1, room) {
+            let pt = map.index_to_point2d(idx);
+            torch(ecs, pt.x, pt.y);
+        }
+    }
+
+    if RNG.roll_die(TRAP_CHANCE) == 1 {
+        for idx in get_spawn_points(map, 1, room) {
+            let pt = map.index_to_point2d(idx);
+            random_trap(ecs, pt.x, pt.y);
+        }
+    }
 }
 
 fn get_spawn_points(map: &Map, count: i32, room: &Rect) -> Vec<usize> {
@@ -273,7 +1071,7 @@ fn get_spawn_points(map: &Map, count: i32, room: &Rect) -> Vec<usize> {
             let x = room.x1 + RNG.range(1, i32::abs(room.x2 - room.x1) - 2);
             let y = room.y1 + RNG.range(1, i32::abs(room.y2 - room.y1) - 2);
             let idx = map.xy_idx(x, y);
-            if !spawn_points.contains(&idx) {
+            if is_safe_spawn_point(map, idx) && !spawn_points.contains(&idx) {
                 spawn_points.insert(0, idx);
                 added = true;
             }
@@ -283,6 +1081,36 @@ fn get_spawn_points(map: &Map, count: i32, room: &Rect) -> Vec<usize> {
     spawn_points
 }
 
+fn is_safe_spawn_point(map: &Map, idx: usize) -> bool {
+    map.is_walkable_idx(idx) && !map.tiles[idx].is_hazardous()
+}
+
+fn get_spawn_points_near(map: &Map, count: i32, room: &Rect, center: Point) -> Vec<usize> {
+    const FORMATION_RADIUS: i32 = 2;
+
+    let mut candidates: Vec<usize> = Vec::new();
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let is_near = i32::abs(x - center.x) <= FORMATION_RADIUS && i32::abs(y - center.y) <= FORMATION_RADIUS;
+            if is_near && (x, y) != (center.x, center.y) && is_safe_spawn_point(map, map.xy_idx(x, y)) {
+                candidates.push(map.xy_idx(x, y));
+            }
+        }
+    }
+
+    let mut spawn_points: Vec<usize> = Vec::new();
+    for _i in 0..count {
+        if candidates.is_empty() {
+            break;
+        }
+
+        let candidate = RNG.range(0, candidates.len() as i32) as usize;
+        spawn_points.push(candidates.remove(candidate));
+    }
+
+    spawn_points
+}
+
 pub fn spawn_global_turn(ecs: &mut World) {
     ecs
         .create_entity()
@@ -293,4 +1121,113 @@ pub fn spawn_global_turn(ecs: &mut World) {
         .build();
 
     ecs.insert(GlobalTurnTimeScore { time_score: 0 });
-}
\ No newline at end of file
+}
+pub enum SpawnRequestType {
+    Ally,
+    Split(Entity),
+    Flash { radius: i32, color: RGB },
+}
+
+pub struct SpawnRequest {
+    position: Point,
+    request_type: SpawnRequestType,
+}
+
+/// Collects entities that systems want created, since systems cannot build marked entities themselves.
+#[derive(Default)]
+pub struct SpawnBuilder {
+    requests: Vec<SpawnRequest>
+}
+
+impl SpawnBuilder {
+    pub fn new() -> SpawnBuilder {
+        SpawnBuilder { requests: Vec::new() }
+    }
+
+    pub fn request_ally(&mut self, position: Point) {
+        self.requests.push(SpawnRequest {
+            position,
+            request_type: SpawnRequestType::Ally,
+        });
+    }
+
+    /// A copy of a single one of the stacked `item`, placed exactly at `position`, see `split_item`.
+    pub fn request_split(&mut self, position: Point, item: Entity) {
+        self.requests.push(SpawnRequest {
+            position,
+            request_type: SpawnRequestType::Split(item),
+        });
+    }
+
+    pub fn request_flash(&mut self, position: Point, radius: i32, color: RGB) {
+        self.requests.push(SpawnRequest {
+            position,
+            request_type: SpawnRequestType::Flash { radius, color },
+        });
+    }
+}
+
+pub fn spawn_requested(ecs: &mut World) {
+    let requests: Vec<SpawnRequest> = ecs.write_resource::<SpawnBuilder>().requests.drain(..).collect();
+
+    for request in requests.iter() {
+        match &request.request_type {
+            SpawnRequestType::Ally => {
+                let spawn_point = find_free_adjacent_tile(&ecs.fetch::<Map>(), request.position);
+
+                match spawn_point {
+                    None => ecs.write_resource::<GameLog>().add("There is no room for anything to appear."),
+                    Some(spawn_point) => wolf(ecs, spawn_point.x, spawn_point.y),
+                }
+            }
+            SpawnRequestType::Split(item) => {
+                split_item(ecs, *item, request.position.x, request.position.y);
+            }
+            SpawnRequestType::Flash { radius, color } => {
+                flash(ecs, request.position.x, request.position.y, *radius, *color);
+            }
+        }
+    }
+}
+
+macro_rules! copy_components {
+    ($ecs:expr, $from:expr, $to:expr, $( $type:ty ),*) => {
+        $(
+        let component = $ecs.read_storage::<$type>().get($from).cloned();
+        if let Some(component) = component {
+            $ecs.write_storage::<$type>().insert($to, component).expect("Unable to copy component");
+        }
+        )*
+    };
+}
+
+/// Creates a single one of a stacked item by copying the stack's components, so that any item can be split.
+fn split_item(ecs: &mut World, item: Entity, x: i32, y: i32) {
+    if !ecs.is_alive(item) {
+        return;
+    }
+
+    let single = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Stackable { quantity: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    copy_components!(
+        ecs, item, single,
+        Item, Name, Renderable, Weight, Price, Consumable, ProvidesHealing, ProvidesFood, Ranged, InflictsDamage,
+        AreaOfEffect, Confusion, SummonsAlly, Charms, Knockback, ThrowDamage, Ammunition, RangedWeapon, Identifies,
+        Charges, Recharges, Enchantment, Cursed, ModifiersKnown, RemovesCurse, MagicMapping, Teleports,
+        DetectsMonsters, LightSource
+    );
+}
+
+fn find_free_adjacent_tile(map: &Map, position: Point) -> Option<Point> {
+    let deltas = [(0, -1), (0, 1), (1, 0), (-1, 0), (1, -1), (1, 1), (-1, -1), (-1, 1)];
+
+    deltas
+        .iter()
+        .map(|(delta_x, delta_y)| Point::new(position.x + delta_x, position.y + delta_y))
+        .find(|point| map.is_valid(point.x, point.y) && !map.is_blocked(point.x, point.y))
+}
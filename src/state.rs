@@ -1,8 +1,8 @@
-use rltk::{GameState, render_draw_buffer, Rltk};
+use rltk::{GameState, render_draw_buffer, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 use specs::WorldExt;
 
-use crate::{console_log, Context, cull_dead_particles, DamageSystem, decide_turn, delete_the_dead, GlobalTurnSystem, gui, ItemCollectionSystem, ItemDropSystem, ItemMenuResult, ItemUseSystem, load_game, MainMenuSelection, MapIndexingSystem, MeleeCombatSystem, MonsterAI, MovementSystem, ParticleSpawnSystem, player_input, Ranged, RangedTargetDrawerSettings, RangedTargetResult, render_camera, save_game, VisibilitySystem, WaitSystem, WantsToDrop, WantsToUseItem};
+use crate::{console_log, Container, ContainerSystem, Context, cull_dead_particles, DamageSystem, decide_turn, DoorSystem, delete_the_dead, GlobalTurnSystem, gui, HearingSystem, ItemCollectionSystem, ItemDropSystem, ItemMenuResult, ItemThrowSystem, ItemUseSystem, LightingSystem, load_game, MainMenuSelection, MapIndexingSystem, MeleeCombatSystem, MonsterAI, MovementSystem, ParticleSpawnSystem, player_input, Ranged, RangedCombatSystem, RangedTargetDrawerSettings, RangedTargetResult, render_camera, save_game, SearchSystem, spawn_requested, THROW_RANGE, TradeSystem, VisibilitySystem, WaitSystem, WantsToBuy, WantsToDrop, WantsToFire, WantsToPutIn, WantsToSell, WantsToTakeOut, WantsToThrow, WantsToUseItem};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum RunState {
@@ -13,11 +13,23 @@ pub enum RunState {
     PlayerTurn,
     ShowInventory,
     ShowDropItem,
-    ShowTargeting { range: i32, item: Entity, radius: Option<i32> },
+    ShowThrowItem,
+    ShowBuyItem { shopkeeper: Entity },
+    ShowSellItem { shopkeeper: Entity },
+    ShowContainer { container: Entity },
+    ShowPutInContainer { container: Entity },
+    ShowTargeting { range: i32, item: Entity, radius: Option<i32>, purpose: TargetingPurpose },
     MainMenu { menu_selection: MainMenuSelection },
     SaveGame,
 }
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TargetingPurpose {
+    UseItem,
+    Throw,
+    Fire,
+}
+
 impl RunState {
     pub fn is_turn(&self) -> bool {
         match self {
@@ -101,6 +113,9 @@ impl GameState for State {
                 match item_menu_result {
                     ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
                     ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected(selected_item) if self.ecs.read_storage::<Container>().contains(selected_item) => {
+                        new_run_state = RunState::ShowContainer { container: selected_item };
+                    }
                     ItemMenuResult::Selected(selected_item) => {
                         let player_entity = self.ecs.read_resource::<Entity>();
                         match self.ecs.read_storage::<Ranged>().get(selected_item) {
@@ -109,6 +124,7 @@ impl GameState for State {
                                     item: selected_item,
                                     range: ranged.range,
                                     radius: None,
+                                    purpose: TargetingPurpose::UseItem,
                                 }
                             }
                             None => {
@@ -139,7 +155,107 @@ impl GameState for State {
                     }
                 }
             }
-            RunState::ShowTargeting { range, item, radius } => {
+            RunState::ShowThrowItem => {
+                let throw_item_menu_result = gui::show_throw_item_menu(self, context);
+
+                match throw_item_menu_result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected(selected_item) => {
+                        new_run_state = RunState::ShowTargeting {
+                            item: selected_item,
+                            range: THROW_RANGE,
+                            radius: None,
+                            purpose: TargetingPurpose::Throw,
+                        }
+                    }
+                }
+            }
+            RunState::ShowBuyItem { shopkeeper } => {
+                let buy_item_menu_result = gui::show_buy_item_menu(self, context, shopkeeper);
+
+                match buy_item_menu_result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {
+                        if context.rltk.key == Some(VirtualKeyCode::Tab) {
+                            new_run_state = RunState::ShowSellItem { shopkeeper };
+                        }
+                    }
+                    ItemMenuResult::Selected(selected_item) => {
+                        let player_entity = self.ecs.read_resource::<Entity>();
+                        let mut wants_to_buy = self.ecs.write_storage::<WantsToBuy>();
+                        wants_to_buy.insert(*player_entity, WantsToBuy {
+                            item: selected_item,
+                            seller: shopkeeper,
+                        }).expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowSellItem { shopkeeper } => {
+                let sell_item_menu_result = gui::show_sell_item_menu(self, context);
+
+                match sell_item_menu_result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {
+                        if context.rltk.key == Some(VirtualKeyCode::Tab) {
+                            new_run_state = RunState::ShowBuyItem { shopkeeper };
+                        }
+                    }
+                    ItemMenuResult::Selected(selected_item) => {
+                        let player_entity = self.ecs.read_resource::<Entity>();
+                        let mut wants_to_sell = self.ecs.write_storage::<WantsToSell>();
+                        wants_to_sell.insert(*player_entity, WantsToSell {
+                            item: selected_item,
+                            buyer: shopkeeper,
+                        }).expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowContainer { container } => {
+                let container_menu_result = gui::show_container_menu(self, context, container);
+
+                match container_menu_result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {
+                        if context.rltk.key == Some(VirtualKeyCode::Tab) {
+                            new_run_state = RunState::ShowPutInContainer { container };
+                        }
+                    }
+                    ItemMenuResult::Selected(selected_item) => {
+                        let player_entity = self.ecs.read_resource::<Entity>();
+                        let mut wants_to_take_out = self.ecs.write_storage::<WantsToTakeOut>();
+                        wants_to_take_out.insert(*player_entity, WantsToTakeOut {
+                            item: selected_item,
+                            container,
+                        }).expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowPutInContainer { container } => {
+                let put_in_menu_result = gui::show_put_in_container_menu(self, context);
+
+                match put_in_menu_result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {
+                        if context.rltk.key == Some(VirtualKeyCode::Tab) {
+                            new_run_state = RunState::ShowContainer { container };
+                        }
+                    }
+                    ItemMenuResult::Selected(selected_item) => {
+                        let player_entity = self.ecs.read_resource::<Entity>();
+                        let mut wants_to_put_in = self.ecs.write_storage::<WantsToPutIn>();
+                        wants_to_put_in.insert(*player_entity, WantsToPutIn {
+                            item: selected_item,
+                            container,
+                        }).expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowTargeting { range, item, radius, purpose } => {
                 let target_result = gui::ranged_target(
                     self,
                     context,
@@ -153,11 +269,29 @@ impl GameState for State {
                     RangedTargetResult::NoResponse => {}
                     RangedTargetResult::Selected(target) => {
                         let player_entity = self.ecs.read_resource::<Entity>();
-                        let mut want_to_use_items = self.ecs.write_storage::<WantsToUseItem>();
-                        want_to_use_items.insert(*player_entity, WantsToUseItem {
-                            item,
-                            target: Some(target),
-                        }).expect("Unable to insert intent");
+                        match purpose {
+                            TargetingPurpose::UseItem => {
+                                let mut want_to_use_items = self.ecs.write_storage::<WantsToUseItem>();
+                                want_to_use_items.insert(*player_entity, WantsToUseItem {
+                                    item,
+                                    target: Some(target),
+                                }).expect("Unable to insert intent");
+                            }
+                            TargetingPurpose::Throw => {
+                                let mut wants_to_throw = self.ecs.write_storage::<WantsToThrow>();
+                                wants_to_throw.insert(*player_entity, WantsToThrow {
+                                    item,
+                                    target,
+                                }).expect("Unable to insert intent");
+                            }
+                            TargetingPurpose::Fire => {
+                                let mut wants_to_fire = self.ecs.write_storage::<WantsToFire>();
+                                wants_to_fire.insert(*player_entity, WantsToFire {
+                                    weapon: item,
+                                    target,
+                                }).expect("Unable to insert intent");
+                            }
+                        }
                         new_run_state = RunState::PlayerTurn;
                     }
                 }
@@ -200,16 +334,24 @@ impl SysRunner {
     pub fn new() -> Self {
         let dispatcher = DispatcherBuilder::new()
             .with(MapIndexingSystem, MapIndexingSystem::NAME, &[])
-            .with(VisibilitySystem, "vis", &[MapIndexingSystem::NAME])
+            .with(LightingSystem, LightingSystem::NAME, &[MapIndexingSystem::NAME])
+            .with(VisibilitySystem, "vis", &[MapIndexingSystem::NAME, LightingSystem::NAME])
             .with(GlobalTurnSystem, "global", &[MapIndexingSystem::NAME])
             .with(MonsterAI, MonsterAI::NAME, &[MapIndexingSystem::NAME])
-            .with(MovementSystem, MovementSystem::NAME, &[MonsterAI::NAME])
             .with(MeleeCombatSystem, "melee_combat", &[MonsterAI::NAME])
             .with(WaitSystem, "wait", &[MonsterAI::NAME])
             .with(ItemCollectionSystem, "pick_up", &[MonsterAI::NAME])
             .with(ItemUseSystem, "use_item", &[MonsterAI::NAME])
+            .with(MovementSystem, MovementSystem::NAME, &[MonsterAI::NAME, "melee_combat", "use_item"])
             .with(ItemDropSystem, "drop", &[MonsterAI::NAME])
-            .with(DamageSystem, "damage", &["melee_combat", "use_item"])
+            .with(ItemThrowSystem, ItemThrowSystem::NAME, &[MonsterAI::NAME])
+            .with(RangedCombatSystem, RangedCombatSystem::NAME, &[MonsterAI::NAME])
+            .with(TradeSystem, "trade", &[MonsterAI::NAME])
+            .with(ContainerSystem, "container", &[MonsterAI::NAME])
+            .with(DoorSystem, DoorSystem::NAME, &[MonsterAI::NAME])
+            .with(SearchSystem, "search", &[MonsterAI::NAME])
+            .with(DamageSystem, "damage", &["global", MovementSystem::NAME, "melee_combat", "use_item", ItemThrowSystem::NAME, RangedCombatSystem::NAME])
+            .with(HearingSystem, HearingSystem::NAME, &[MovementSystem::NAME, "melee_combat", "use_item", ItemThrowSystem::NAME, RangedCombatSystem::NAME])
             .with(ParticleSpawnSystem, "particle", &["damage"])
             .build();
 
@@ -219,6 +361,7 @@ impl SysRunner {
     pub fn run(&mut self, ecs: &mut World) {
         self.dispatcher.dispatch(ecs);
         ecs.maintain();
+        spawn_requested(ecs);
     }
 }
 
@@ -3,7 +3,7 @@ extern crate specs;
 use rltk::Point;
 use specs::prelude::*;
 
-use crate::{CombatStats, GameLog, MEDIUM_LIFETIME, Name, ParticleBuilder, Player, Position, RunStateHolder, SuffersDamage};
+use crate::{Asleep, CombatStats, corpse, Fleeing, GameLog, InBackpack, IsVisible, Kills, LootTable, Map, MEDIUM_LIFETIME, Name, PackMember, ParticleBuilder, Player, Position, Resistances, roll_loot, RunStateHolder, SlainBy, spawn_named_item, SuffersDamage, Vulnerabilities, write_morgue_file};
 
 pub struct DamageSystem;
 
@@ -14,6 +14,13 @@ impl<'a> System<'a> for DamageSystem {
         WriteStorage<'a, SuffersDamage>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Vulnerabilities>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, IsVisible>,
+        WriteStorage<'a, SlainBy>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -23,14 +30,48 @@ impl<'a> System<'a> for DamageSystem {
             mut suffers_damage,
             mut particle_builder,
             positions,
+            mut asleep,
+            resistances,
+            vulnerabilities,
+            mut game_log,
+            names,
+            is_visible,
+            mut slain_by,
         ) = data;
 
         for (entity, mut combat_stat, suffer_damage) in (&entities, &mut combat_stats, &suffers_damage).join() {
-            if suffer_damage.amount == 0 {
+            let mut total_damage = 0;
+
+            for instance in suffer_damage.instances.iter() {
+                let is_resisted = resistances.get(entity).is_some_and(|resistances| resistances.kinds.contains(&instance.kind));
+                let is_vulnerable = vulnerabilities.get(entity).is_some_and(|vulnerabilities| vulnerabilities.kinds.contains(&instance.kind));
+
+                let amount = match (is_resisted, is_vulnerable) {
+                    (true, false) => instance.amount / 2,
+                    (false, true) => instance.amount * 2,
+                    _ => instance.amount,
+                };
+
+                if amount != instance.amount && is_visible.get(entity).is_some() {
+                    if let Some(name) = names.get(entity) {
+                        let verb = if is_resisted { "resists" } else { "is vulnerable to" };
+                        game_log.add(format!("{} {} {} damage ({} hp).", name.name, verb, instance.kind, amount));
+                    }
+                }
+
+                if combat_stat.hp > 0 && combat_stat.hp - amount <= 0 {
+                    slain_by.insert(entity, SlainBy { killer: instance.source, cause: instance.cause }).expect("Unable to insert slain by");
+                }
+
+                combat_stat.hp -= amount;
+                total_damage += amount;
+            }
+
+            if total_damage == 0 {
                 continue;
             }
 
-            combat_stat.hp -= suffer_damage.amount;
+            asleep.remove(entity);
 
             if let Some(position) = positions.get(entity) {
                 particle_builder.request_aura(
@@ -49,24 +90,37 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut player_slain_by: Option<SlainBy> = None;
 
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let names = ecs.read_storage::<Name>();
         let entities = ecs.entities();
         let players = ecs.read_storage::<Player>();
+        let mut slain_by = ecs.write_storage::<SlainBy>();
+        let mut kills = ecs.write_storage::<Kills>();
         let mut game_log = ecs.write_resource::<GameLog>();
         let run_state_holder = ecs.read_resource::<RunStateHolder>();
 
         for (entity, stats) in (&entities, &combat_stats).join() {
             if stats.hp <= 0 {
                 let player_or_null = players.get(entity);
+                let slain = slain_by.remove(entity);
+                let killer = slain.as_ref().and_then(|slain| slain.killer);
+                let cause = slain.as_ref().and_then(|slain| slain.cause);
 
                 match player_or_null {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            game_log.add(format!("{} is dead", victim_name.name));
+                            match killer.and_then(|killer| names.get(killer)).map(|name| name.name.as_str()).or(cause) {
+                                Some(killer_name) => game_log.add(format!("{} is slain by {}", victim_name.name, killer_name)),
+                                None => game_log.add(format!("{} is dead", victim_name.name)),
+                            }
+
+                            if let Some(killer_kills) = killer.and_then(|killer| kills.get_mut(killer)) {
+                                killer_kills.victims.push(victim_name.name.to_string());
+                            }
                         }
                         dead.push(entity);
                     }
@@ -74,13 +128,150 @@ pub fn delete_the_dead(ecs: &mut World) {
                         if run_state_holder.run_state.is_turn() {
                             game_log.add("You are dead".to_string());
                         }
+
+                        // Only the killing blow leaves a SlainBy, so the morgue file is written once.
+                        player_slain_by = slain;
                     }
                 }
             }
         }
     }
 
+    if let Some(slain_by) = player_slain_by {
+        write_morgue_file(ecs, &slain_by);
+    }
+
+    scatter_leaderless_packs(ecs, &dead);
+    leave_remains(ecs, &dead);
+
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
-}
\ No newline at end of file
+}
+
+fn scatter_leaderless_packs(ecs: &mut World, dead: &[Entity]) {
+    const FLEE_TURNS: i32 = 8;
+
+    let entities = ecs.entities();
+    let mut pack_members = ecs.write_storage::<PackMember>();
+    let mut fleeings = ecs.write_storage::<Fleeing>();
+    let is_visible = ecs.read_storage::<IsVisible>();
+    let names = ecs.read_storage::<Name>();
+    let mut game_log = ecs.write_resource::<GameLog>();
+
+    for leader in dead.iter() {
+        let followers = (&entities, &pack_members)
+            .join()
+            .filter(|(_, pack_member)| pack_member.leader == *leader)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<Entity>>();
+
+        if followers.is_empty() {
+            continue;
+        }
+
+        let is_witnessed = is_visible.get(*leader).is_some() || followers.iter().any(|follower| is_visible.get(*follower).is_some());
+        if is_witnessed {
+            if let Some(leader_name) = names.get(*leader) {
+                game_log.add(format!("With {} dead, the war party scatters!", leader_name.name));
+            }
+        }
+
+        for follower in followers {
+            pack_members.remove(follower);
+            fleeings.insert(follower, Fleeing { turns: FLEE_TURNS }).expect("Unable to insert fleeing");
+        }
+    }
+}
+
+struct Remains {
+    position: Point,
+    name: String,
+    carried: Vec<Entity>,
+    loot: Vec<String>,
+    is_witnessed: bool,
+}
+
+fn leave_remains(ecs: &mut World, dead: &[Entity]) {
+    let mut all_remains = Vec::new();
+
+    {
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let names = ecs.read_storage::<Name>();
+        let in_backpacks = ecs.read_storage::<InBackpack>();
+        let loot_tables = ecs.read_storage::<LootTable>();
+        let is_visible = ecs.read_storage::<IsVisible>();
+
+        for victim in dead.iter() {
+            let position = match positions.get(*victim) {
+                None => continue,
+                Some(position) => Point::new(position.x, position.y),
+            };
+
+            let carried = (&entities, &in_backpacks)
+                .join()
+                .filter(|(_, in_backpack)| in_backpack.owner == *victim)
+                .map(|(item, _)| item)
+                .collect();
+
+            all_remains.push(Remains {
+                position,
+                name: names.get(*victim).map_or("Something".to_string(), |name| name.name.to_string()),
+                carried,
+                loot: loot_tables.get(*victim).map_or(Vec::new(), roll_loot),
+                is_witnessed: is_visible.get(*victim).is_some(),
+            });
+        }
+    }
+
+    for remains in all_remains.iter() {
+        corpse(ecs, remains.position.x, remains.position.y, &remains.name);
+
+        let drop_spots = find_drop_spots(&ecs.fetch::<Map>(), remains.position);
+        let mut dropped: Vec<String> = Vec::new();
+
+        {
+            let mut positions = ecs.write_storage::<Position>();
+            let mut in_backpacks = ecs.write_storage::<InBackpack>();
+            let names = ecs.read_storage::<Name>();
+
+            for (i, item) in remains.carried.iter().enumerate() {
+                let spot = drop_spots[i % drop_spots.len()];
+                in_backpacks.remove(*item);
+                positions.insert(*item, Position { x: spot.x, y: spot.y }).expect("Unable to insert position");
+
+                if let Some(name) = names.get(*item) {
+                    dropped.push(name.name.to_string());
+                }
+            }
+        }
+
+        for (i, item_name) in remains.loot.iter().enumerate() {
+            let spot = drop_spots[(remains.carried.len() + i) % drop_spots.len()];
+            if spawn_named_item(ecs, item_name, spot.x, spot.y) {
+                dropped.push(item_name.to_string());
+            }
+        }
+
+        if remains.is_witnessed && !dropped.is_empty() {
+            ecs.write_resource::<GameLog>().add(format!("{} drops {}.", remains.name, dropped.join(", ")));
+        }
+    }
+}
+
+/// The victim's own tile, followed by any free neighbouring tiles items can be scattered to.
+fn find_drop_spots(map: &Map, position: Point) -> Vec<Point> {
+    let deltas = [(0, -1), (0, 1), (1, 0), (-1, 0), (1, -1), (1, 1), (-1, -1), (-1, 1)];
+
+    let mut drop_spots = vec![position];
+
+    for (delta_x, delta_y) in deltas.iter() {
+        let (x, y) = (position.x + delta_x, position.y + delta_y);
+        if map.is_valid(x, y) && map.is_walkable_idx(map.xy_idx(x, y)) && !map.is_blocked(x, y) {
+            drop_spots.push(Point::new(x, y));
+        }
+    }
+
+    drop_spots
+}
@@ -0,0 +1,148 @@
+extern crate specs;
+
+use specs::prelude::*;
+
+use crate::{backpack_weight, BlocksTile, BlocksVisibility, CanMove, CombatStats, Door, effective_move_cost, GameLog, InBackpack, InContainer, IsVisible, Map, Name, Position, Renderable, Stackable, TakesTurn, Viewshed, WantsToCloseDoor, WantsToOpenDoor, Weight};
+
+pub const OPEN_DOOR_GLYPH: char = '/';
+pub const CLOSED_DOOR_GLYPH: char = '+';
+
+/// Opens and closes doors. Opening or closing one takes as long as a step.
+pub struct DoorSystem;
+
+impl DoorSystem {
+    pub const NAME: &'static str = "door";
+}
+
+impl<'a> System<'a> for DoorSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToOpenDoor>,
+        WriteStorage<'a, WantsToCloseDoor>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, BlocksTile>,
+        WriteStorage<'a, BlocksVisibility>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, TakesTurn>,
+        ReadStorage<'a, CanMove>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, IsVisible>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, Stackable>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, InContainer>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut game_log,
+            mut map,
+            entities,
+            mut wants_to_open_door,
+            mut wants_to_close_door,
+            mut doors,
+            mut blocks_tiles,
+            mut blocks_visibility,
+            mut renderables,
+            mut viewsheds,
+            mut takes_turn,
+            can_move,
+            positions,
+            is_visible,
+            names,
+            in_backpacks,
+            weights,
+            stackables,
+            combat_stats,
+            in_containers,
+        ) = data;
+
+        let move_cost = |entity: Entity, can_move: &CanMove| -> u32 {
+            let carried = backpack_weight(&in_backpacks, &in_containers, &weights, &stackables, entity);
+            effective_move_cost(can_move, combat_stats.get(entity), carried)
+        };
+
+        let mut is_changed = false;
+
+        for (entity, to_open, takes_turn, can_move) in (&entities, &wants_to_open_door, &mut takes_turn, &can_move).join() {
+            takes_turn.time_score += move_cost(entity, can_move);
+
+            let (door, position) = match (doors.get_mut(to_open.door), positions.get(to_open.door)) {
+                (Some(door), Some(position)) if !door.open => (door, position),
+                _ => continue,
+            };
+
+            door.open = true;
+            blocks_tiles.remove(to_open.door);
+            blocks_visibility.remove(to_open.door);
+            if let Some(renderable) = renderables.get_mut(to_open.door) {
+                renderable.glyph = rltk::to_cp437(OPEN_DOOR_GLYPH);
+            }
+
+            let idx = map.xy_idx(position.x, position.y);
+            map.blocked[idx] = false;
+            map.view_blocked[idx] = false;
+            is_changed = true;
+
+            if entity == *player_entity {
+                game_log.add("You open the door.".to_string());
+            } else if is_visible.get(entity).is_some() {
+                let name = names.get(entity).map_or("Something", |name| &name.name);
+                game_log.add(format!("{} opens a door.", name));
+            }
+        }
+
+        wants_to_open_door.clear();
+
+        for (entity, to_close, takes_turn, can_move) in (&entities, &wants_to_close_door, &mut takes_turn, &can_move).join() {
+            takes_turn.time_score += move_cost(entity, can_move);
+
+            let (door, position) = match (doors.get_mut(to_close.door), positions.get(to_close.door)) {
+                (Some(door), Some(position)) if door.open => (door, position),
+                _ => continue,
+            };
+
+            let idx = map.xy_idx(position.x, position.y);
+            let is_doorway_clear = map.tile_content[idx].iter().all(|other| *other == to_close.door);
+            if !is_doorway_clear {
+                if entity == *player_entity {
+                    game_log.add("Something is in the way.".to_string());
+                }
+                continue;
+            }
+
+            door.open = false;
+            blocks_tiles.insert(to_close.door, BlocksTile).expect("Unable to insert blocker");
+            blocks_visibility.insert(to_close.door, BlocksVisibility).expect("Unable to insert blocker");
+            if let Some(renderable) = renderables.get_mut(to_close.door) {
+                renderable.glyph = rltk::to_cp437(CLOSED_DOOR_GLYPH);
+            }
+
+            map.blocked[idx] = true;
+            map.view_blocked[idx] = true;
+            is_changed = true;
+
+            if entity == *player_entity {
+                game_log.add("You close the door.".to_string());
+            } else if is_visible.get(entity).is_some() {
+                let name = names.get(entity).map_or("Something", |name| &name.name);
+                game_log.add(format!("{} closes a door.", name));
+            }
+        }
+
+        wants_to_close_door.clear();
+
+        if is_changed {
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
@@ -3,7 +3,11 @@ extern crate specs;
 
 use specs::prelude::*;
 
-use crate::{Confusion, console_log, GlobalTurn, GlobalTurnTimeScore, TakesTurn, WantsToTakeTurn};
+use crate::{CombatStats, Confusion, console_log, DamageKind, Flash, Fleeing, GameLog, GlobalTurn, GlobalTurnTimeScore, HungerClock, HungerState, Player, RNG, SuffersDamage, TakesTurn, Telepathy, WantsToTakeTurn};
+
+pub const HUNGER_STATE_DURATION: i32 = 300;
+const STARVATION_DAMAGE: i32 = 1;
+const STARVATION_MESSAGE_CHANCE: i32 = 10;
 
 pub struct GlobalTurnSystem;
 
@@ -14,7 +18,15 @@ impl<'a> System<'a> for GlobalTurnSystem {
         ReadStorage<'a, GlobalTurn>,
         WriteExpect<'a, GlobalTurnTimeScore>,
         WriteStorage<'a, Confusion>,
-        Entities<'a>
+        Entities<'a>,
+        WriteStorage<'a, Fleeing>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, HungerClock>,
+        WriteStorage<'a, SuffersDamage>,
+        ReadStorage<'a, Player>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Telepathy>,
+        WriteStorage<'a, Flash>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -25,6 +37,14 @@ impl<'a> System<'a> for GlobalTurnSystem {
             mut global_turn_time_score,
             mut confusions,
             entities,
+            mut fleeings,
+            mut combat_stats,
+            mut hunger_clocks,
+            mut suffers_damage,
+            players,
+            mut game_log,
+            mut telepathies,
+            mut flashes,
         ) = data;
 
         const TIME_SCORE_LIMIT: u32 = 1000;
@@ -65,6 +85,92 @@ impl<'a> System<'a> for GlobalTurnSystem {
             for entity in confusions_to_remove.iter() {
                 confusions.remove(*entity);
             }
+
+            let mut fleeings_to_remove = Vec::new();
+            {
+                for (_, fleeing, entity) in (&takes_turn, &mut fleeings, &entities).join() {
+                    fleeing.turns -= 1;
+
+                    if fleeing.turns <= 0 {
+                        fleeings_to_remove.push(entity);
+                    }
+                }
+            }
+            for entity in fleeings_to_remove.iter() {
+                fleeings.remove(*entity);
+            }
+
+            let mut telepathies_to_remove = Vec::new();
+            {
+                for (_, telepathy, entity) in (&takes_turn, &mut telepathies, &entities).join() {
+                    telepathy.turns -= 1;
+
+                    if telepathy.turns <= 0 {
+                        telepathies_to_remove.push(entity);
+                    }
+                }
+            }
+            for entity in telepathies_to_remove.iter() {
+                telepathies.remove(*entity);
+
+                if players.get(*entity).is_some() {
+                    game_log.add("You no longer sense the presence of monsters.".to_string());
+                }
+            }
+
+            for (entity, flash) in (&entities, &mut flashes).join() {
+                flash.turns -= 1;
+
+                if flash.turns <= 0 {
+                    entities.delete(entity).expect("Unable to delete flash");
+                }
+            }
+
+            for (entity, hunger_clock) in (&entities, &mut hunger_clocks).join() {
+                let is_player = players.get(entity).is_some();
+
+                hunger_clock.duration -= 1;
+
+                if hunger_clock.duration > 0 {
+                    continue;
+                }
+
+                match hunger_clock.state {
+                    HungerState::WellFed => {
+                        hunger_clock.state = HungerState::Normal;
+                        hunger_clock.duration = HUNGER_STATE_DURATION;
+                        if is_player {
+                            game_log.add("You are no longer well fed.".to_string());
+                        }
+                    }
+                    HungerState::Normal => {
+                        hunger_clock.state = HungerState::Hungry;
+                        hunger_clock.duration = HUNGER_STATE_DURATION;
+                        if is_player {
+                            game_log.add("You are hungry.".to_string());
+                        }
+                    }
+                    HungerState::Hungry => {
+                        hunger_clock.state = HungerState::Starving;
+                        hunger_clock.duration = 0;
+                        if is_player {
+                            game_log.add("You are starving!".to_string());
+                        }
+                    }
+                    HungerState::Starving => {
+                        if is_player && RNG.roll_die(STARVATION_MESSAGE_CHANCE) == 1 {
+                            game_log.add("Your hunger pangs are getting painful!".to_string());
+                        }
+                        SuffersDamage::new_damage(&mut suffers_damage, entity, STARVATION_DAMAGE, DamageKind::Physical, None);
+                    }
+                }
+            }
+
+            for stats in (&mut combat_stats).join() {
+                if stats.hp > 0 && stats.hp < stats.max_hp && stats.regen_rate > 0 && RNG.roll_die(stats.regen_rate) == 1 {
+                    stats.hp += 1;
+                }
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+extern crate specs;
+
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::{Asleep, Faction, GameLog, Hearing, Investigating, is_hostile, IsVisible, Map, MEDIUM_LIFETIME, Name, ParticleBuilder, Position};
+
+pub const FOOTSTEP_VOLUME: i32 = 2;
+pub const IMPACT_VOLUME: i32 = 4;
+pub const SPELL_VOLUME: i32 = 6;
+pub const MELEE_VOLUME: i32 = 8;
+pub const EXPLOSION_VOLUME: i32 = 20;
+
+pub struct NoiseRequest {
+    position: Point,
+    volume: i32,
+    source: Option<Entity>,
+}
+
+#[derive(Default)]
+pub struct NoiseBuilder {
+    requests: Vec<NoiseRequest>
+}
+
+impl NoiseBuilder {
+    pub fn new() -> NoiseBuilder {
+        NoiseBuilder { requests: Vec::new() }
+    }
+
+    /// A noise of `volume` travels that many steps along walkable tiles from `position`.
+    pub fn request_noise(&mut self, position: Point, volume: i32, source: Option<Entity>) {
+        self.requests.push(NoiseRequest {
+            position,
+            volume,
+            source,
+        });
+    }
+}
+
+pub struct HearingSystem;
+
+impl HearingSystem {
+    pub const NAME: &'static str = "hearing";
+}
+
+impl<'a> System<'a> for HearingSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadStorage<'a, Hearing>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, IsVisible>,
+        WriteStorage<'a, Asleep>,
+        WriteStorage<'a, Investigating>,
+        ReadStorage<'a, Faction>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            mut noise_builder,
+            mut game_log,
+            mut particle_builder,
+            hearings,
+            positions,
+            names,
+            is_visible,
+            mut asleep,
+            mut investigating,
+            factions,
+        ) = data;
+
+        for noise in noise_builder.requests.iter() {
+            let mut distances = vec![None; map.count()];
+            for (idx, distance) in map.walkable_distances(noise.position, noise.volume) {
+                distances[idx] = Some(distance);
+            }
+
+            for (entity, hearing, position) in (&entities, &hearings, &positions).join() {
+                // Creatures only pay attention to noises made by their enemies, or by nobody in particular.
+                let is_hostile_source = match noise.source {
+                    None => true,
+                    Some(source) => match (factions.get(entity), factions.get(source)) {
+                        (Some(own_faction), Some(source_faction)) => is_hostile(own_faction.kind, source_faction.kind),
+                        _ => false,
+                    },
+                };
+
+                if !is_hostile_source {
+                    continue;
+                }
+
+                let idx = map.xy_idx(position.x, position.y);
+                let is_heard = match distances[idx] {
+                    Some(distance) => distance <= hearing.radius,
+                    None => false,
+                };
+
+                if !is_heard {
+                    continue;
+                }
+
+                if asleep.remove(entity).is_some() && is_visible.get(entity).is_some() {
+                    if let Some(name) = names.get(entity) {
+                        game_log.add(format!("{} wakes up.", name.name));
+                    }
+
+                    particle_builder.request_aura(
+                        Point::new(position.x, position.y),
+                        MEDIUM_LIFETIME,
+                        rltk::RGB::named(rltk::YELLOW),
+                        rltk::to_cp437('!'),
+                    );
+                }
+
+                investigating
+                    .insert(entity, Investigating { destination: noise.position })
+                    .expect("Unable to insert investigation");
+            }
+        }
+
+        noise_builder.requests.clear();
+    }
+}
@@ -0,0 +1,111 @@
+extern crate specs;
+
+use specs::prelude::*;
+
+use crate::{backpack_weight, carry_capacity, CombatStats, Container, Cursed, Equipped, GameLog, HARD_CARRY_LIMIT, InBackpack, InContainer, item_weight, MagicItemNames, Name, put_in_backpack, Stackable, WantsToPutIn, WantsToTakeOut, Weight};
+
+/// Moves items between backpacks and containers. A container's `Weight` is its empty weight, see `item_weight`.
+pub struct ContainerSystem;
+
+impl<'a> System<'a> for ContainerSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, MagicItemNames>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToTakeOut>,
+        WriteStorage<'a, WantsToPutIn>,
+        WriteStorage<'a, InContainer>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        ReadStorage<'a, Weight>,
+        WriteStorage<'a, Equipped>,
+        ReadStorage<'a, Container>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut game_log,
+            magic_item_names,
+            entities,
+            mut wants_to_take_out,
+            mut wants_to_put_in,
+            mut in_containers,
+            mut in_backpacks,
+            mut stackables,
+            weights,
+            mut equipped,
+            containers,
+            cursed,
+            names,
+            combat_stats,
+        ) = data;
+
+        for (entity, to_take_out) in (&entities, &wants_to_take_out).join() {
+            let is_inside = in_containers.get(to_take_out.item).is_some_and(|in_container| in_container.container == to_take_out.container);
+            if !is_inside {
+                continue;
+            }
+
+            let item_weight = item_weight(&in_containers, &weights, &stackables, to_take_out.item);
+            let item_name = magic_item_names.display_name(&names.get(to_take_out.item).unwrap().name);
+
+            // Taking something out of a carried bag doesn't change the carried weight, since its contents already count.
+            let is_carried_container = in_backpacks.get(to_take_out.container).is_some_and(|in_backpack| in_backpack.owner == entity);
+            if let Some(stats) = combat_stats.get(entity).filter(|_| !is_carried_container) {
+                let carried = backpack_weight(&in_backpacks, &in_containers, &weights, &stackables, entity);
+
+                if carried + item_weight > carry_capacity(stats) * HARD_CARRY_LIMIT {
+                    if entity == *player_entity {
+                        game_log.add(format!("You can't carry the {}, it's too heavy.", item_name));
+                    }
+                    continue;
+                }
+            }
+
+            in_containers.remove(to_take_out.item);
+            put_in_backpack(&entities, &mut in_backpacks, &names, &mut stackables, entity, to_take_out.item);
+
+            if entity == *player_entity {
+                game_log.add(format!("You take out the {}.", item_name));
+            }
+        }
+
+        wants_to_take_out.clear();
+
+        for (entity, to_put_in) in (&entities, &wants_to_put_in).join() {
+            let item_name = magic_item_names.display_name(&names.get(to_put_in.item).unwrap().name);
+
+            if containers.contains(to_put_in.item) {
+                if entity == *player_entity {
+                    game_log.add(format!("The {} won't fit inside another container.", item_name));
+                }
+                continue;
+            }
+
+            if equipped.contains(to_put_in.item) && cursed.contains(to_put_in.item) {
+                if entity == *player_entity {
+                    game_log.add(format!("You can't let go of the {}, it's cursed!", item_name));
+                }
+                continue;
+            }
+
+            in_backpacks.remove(to_put_in.item);
+            equipped.remove(to_put_in.item);
+            in_containers
+                .insert(to_put_in.item, InContainer { container: to_put_in.container })
+                .expect("Unable to insert container entry");
+
+            if entity == *player_entity {
+                let container_name = &names.get(to_put_in.container).unwrap().name;
+                game_log.add(format!("You put the {} in the {}.", item_name, container_name));
+            }
+        }
+
+        wants_to_put_in.clear();
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::Deref;
+
+use specs::prelude::*;
+use specs::storage::MaskedStorage;
+
+use crate::{CanMove, CombatStats, InBackpack, InContainer, Stackable, Weight};
+
+const BASE_CARRY_CAPACITY: f32 = 10.;
+const CARRY_CAPACITY_PER_POWER: f32 = 4.;
+/// Nothing more can be picked up once the carried weight would pass this multiple of the capacity.
+pub const HARD_CARRY_LIMIT: f32 = 2.;
+/// Moving while carrying more than the capacity costs an extra `time_cost / ENCUMBRANCE_MOVE_PENALTY_DIVISOR`.
+const ENCUMBRANCE_MOVE_PENALTY_DIVISOR: u32 = 2;
+
+pub fn carry_capacity(stats: &CombatStats) -> f32 {
+    BASE_CARRY_CAPACITY + stats.power as f32 * CARRY_CAPACITY_PER_POWER
+}
+
+/// The time one step takes, slowed down when carrying more than the capacity.
+/// Anything that charges movement time should go through this.
+pub fn effective_move_cost(can_move: &CanMove, stats: Option<&CombatStats>, carried: f32) -> u32 {
+    if stats.is_some_and(|stats| carried > carry_capacity(stats)) {
+        can_move.time_cost + can_move.time_cost / ENCUMBRANCE_MOVE_PENALTY_DIVISOR
+    } else {
+        can_move.time_cost
+    }
+}
+
+/// Sums item weights, counting every item in a stack.
+pub fn total_weight<'a, I>(items: I) -> f32
+    where I: Iterator<Item=(&'a Weight, Option<&'a Stackable>)> {
+    items
+        .map(|(weight, stackable)| weight.weight * stackable.map_or(1, |stackable| stackable.quantity) as f32)
+        .sum()
+}
+
+pub fn carried_weight(ecs: &World, owner: Entity) -> f32 {
+    backpack_weight(&ecs.read_storage::<InBackpack>(), &ecs.read_storage::<InContainer>(), &ecs.read_storage::<Weight>(), &ecs.read_storage::<Stackable>(), owner)
+}
+
+/// The total weight of everything in the owner's backpack, including what is inside any carried container,
+/// for use with storages already fetched by a system.
+pub fn backpack_weight<B, C, W, S>(in_backpacks: &Storage<InBackpack, B>, in_containers: &Storage<InContainer, C>, weights: &Storage<Weight, W>, stackables: &Storage<Stackable, S>, owner: Entity) -> f32
+    where B: Deref<Target=MaskedStorage<InBackpack>>,
+          C: Deref<Target=MaskedStorage<InContainer>>,
+          W: Deref<Target=MaskedStorage<Weight>>,
+          S: Deref<Target=MaskedStorage<Stackable>> {
+    let carried = total_weight((in_backpacks, weights, stackables.maybe())
+        .join()
+        .filter(|(in_backpack, _, _)| in_backpack.owner == owner)
+        .map(|(_, weight, stackable)| (weight, stackable)));
+
+    let contained = total_weight((in_containers, weights, stackables.maybe())
+        .join()
+        .filter(|(in_container, _, _)| in_backpacks.get(in_container.container).is_some_and(|in_backpack| in_backpack.owner == owner))
+        .map(|(_, weight, stackable)| (weight, stackable)));
+
+    carried + contained
+}
+
+/// The weight of a single item or stack, plus whatever is inside it if it is a container.
+pub fn item_weight<C, W, S>(in_containers: &Storage<InContainer, C>, weights: &Storage<Weight, W>, stackables: &Storage<Stackable, S>, item: Entity) -> f32
+    where C: Deref<Target=MaskedStorage<InContainer>>,
+          W: Deref<Target=MaskedStorage<Weight>>,
+          S: Deref<Target=MaskedStorage<Stackable>> {
+    let own = total_weight(weights.get(item).map(|weight| (weight, stackables.get(item))).into_iter());
+
+    let contained = total_weight((in_containers, weights, stackables.maybe())
+        .join()
+        .filter(|(in_container, _, _)| in_container.container == item)
+        .map(|(_, weight, stackable)| (weight, stackable)));
+
+    own + contained
+}
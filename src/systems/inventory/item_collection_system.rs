@@ -2,7 +2,7 @@ extern crate specs;
 
 use specs::prelude::*;
 
-use crate::{GameLog, InBackpack, Name, Position, WantsToPickUp};
+use crate::{backpack_weight, carry_capacity, CombatStats, GameLog, Gold, HARD_CARRY_LIMIT, InBackpack, InContainer, item_weight, MagicItemNames, Name, Position, Stackable, Wallet, WantsToPickUp, Weight};
 
 pub struct ItemCollectionSystem;
 
@@ -14,6 +14,14 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, WantsToPickUp>,
         WriteStorage<'a, InBackpack>,
         ReadStorage<'a, Name>,
+        WriteStorage<'a, Stackable>,
+        Entities<'a>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, CombatStats>,
+        ReadExpect<'a, MagicItemNames>,
+        ReadStorage<'a, Gold>,
+        WriteStorage<'a, Wallet>,
+        ReadStorage<'a, InContainer>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -23,21 +31,76 @@ impl<'a> System<'a> for ItemCollectionSystem {
             mut positions,
             mut wants_to_pick_up,
             mut in_backpack,
-            names) = data;
+            names,
+            mut stackables,
+            entities,
+            weights,
+            combat_stats,
+            magic_item_names,
+            gold,
+            mut wallets,
+            in_containers) = data;
 
 
         for pick_up in wants_to_pick_up.join() {
+            let item_name = &names.get(pick_up.item).unwrap().name;
+
+            if let (Some(gold), Some(wallet)) = (gold.get(pick_up.item), wallets.get_mut(pick_up.collected_by)) {
+                wallet.gold += gold.amount;
+                entities.delete(pick_up.item).expect("Delete failed");
+
+                if pick_up.collected_by == *player_entity {
+                    game_log.add(format!("You pick up {} gold.", gold.amount));
+                }
+                continue;
+            }
+
+            if let Some(stats) = combat_stats.get(pick_up.collected_by) {
+                let carried = backpack_weight(&in_backpack, &in_containers, &weights, &stackables, pick_up.collected_by);
+                let picked_up = item_weight(&in_containers, &weights, &stackables, pick_up.item);
+
+                if carried + picked_up > carry_capacity(stats) * HARD_CARRY_LIMIT {
+                    if pick_up.collected_by == *player_entity {
+                        game_log.add(format!("You can't carry the {}, it's too heavy.", magic_item_names.display_name(item_name)));
+                    }
+                    continue;
+                }
+            }
+
             positions.remove(pick_up.item);
-            in_backpack
-                .insert(pick_up.item, InBackpack { owner: pick_up.collected_by })
-                .expect("Unable to insert backpack entry");
+            put_in_backpack(&entities, &mut in_backpack, &names, &mut stackables, pick_up.collected_by, pick_up.item);
 
             if pick_up.collected_by == *player_entity {
-                let item_name = &names.get(pick_up.item).unwrap().name;
-                game_log.add(format!("You picked up {}!", item_name));
+                game_log.add(format!("You picked up {}!", magic_item_names.display_name(item_name)));
             }
         }
 
         wants_to_pick_up.clear();
     }
-}
\ No newline at end of file
+}
+
+/// Puts the item in the owner's backpack, merging it into a stack of the same name if they already carry one.
+pub fn put_in_backpack(entities: &Entities, in_backpacks: &mut WriteStorage<InBackpack>, names: &ReadStorage<Name>, stackables: &mut WriteStorage<Stackable>, owner: Entity, item: Entity) {
+    let item_name = &names.get(item).unwrap().name;
+
+    let existing_stack = match stackables.get(item) {
+        None => None,
+        Some(_) => (entities, &*in_backpacks, names, &*stackables)
+            .join()
+            .find(|(stack, in_backpack, name, _)| *stack != item && in_backpack.owner == owner && name.name == *item_name)
+            .map(|(stack, _, _, _)| stack),
+    };
+
+    match existing_stack {
+        Some(existing_stack) => {
+            let quantity = stackables.get(item).unwrap().quantity;
+            stackables.get_mut(existing_stack).unwrap().quantity += quantity;
+            entities.delete(item).expect("Delete failed");
+        }
+        None => {
+            in_backpacks
+                .insert(item, InBackpack { owner })
+                .expect("Unable to insert backpack entry");
+        }
+    }
+}
@@ -1,8 +1,9 @@
 extern crate specs;
 
+use rltk::Point;
 use specs::prelude::*;
 
-use crate::{GameLog, InBackpack, Name, Position, WantsToDrop};
+use crate::{Cursed, Equipped, GameLog, InBackpack, MagicItemNames, Name, Position, SpawnBuilder, Stackable, WantsToDrop};
 
 pub struct ItemDropSystem;
 
@@ -14,7 +15,12 @@ impl<'a> System<'a> for ItemDropSystem {
         WriteStorage::<'a, WantsToDrop>,
         ReadStorage::<'a, Name>,
         WriteStorage::<'a, Position>,
-        WriteStorage::<'a, InBackpack>);
+        WriteStorage::<'a, InBackpack>,
+        WriteStorage::<'a, Equipped>,
+        WriteStorage::<'a, Stackable>,
+        WriteExpect::<'a, SpawnBuilder>,
+        ReadExpect::<'a, MagicItemNames>,
+        ReadStorage::<'a, Cursed>);
 
     fn run(&mut self, data: Self::SystemData) {
         let (
@@ -24,23 +30,41 @@ impl<'a> System<'a> for ItemDropSystem {
             mut wants_to_drop,
             names,
             mut positions,
-            mut in_backpacks
+            mut in_backpacks,
+            mut equipped,
+            mut stackables,
+            mut spawn_builder,
+            magic_item_names,
+            cursed,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_to_drop).join() {
             let drop_position: Position;
             { drop_position = *positions.get(entity).unwrap(); }
 
-            positions.insert(to_drop.item, Position {
-                x: drop_position.x,
-                y: drop_position.y,
-            }).expect("Unable to insert position");
+            let name = &names.get(to_drop.item).unwrap().name;
 
-            in_backpacks.remove(to_drop.item).expect("Unable to remove from backpack");
+            if equipped.contains(to_drop.item) && cursed.contains(to_drop.item) {
+                if entity == *player_entity {
+                    game_log.add(format!("You can't let go of the {}, it's cursed!", name));
+                }
+                continue;
+            }
+
+            if Stackable::split_one(&mut stackables, to_drop.item) {
+                spawn_builder.request_split(Point::new(drop_position.x, drop_position.y), to_drop.item);
+            } else {
+                positions.insert(to_drop.item, Position {
+                    x: drop_position.x,
+                    y: drop_position.y,
+                }).expect("Unable to insert position");
+
+                in_backpacks.remove(to_drop.item).expect("Unable to remove from backpack");
+                equipped.remove(to_drop.item);
+            }
 
-            let name = &names.get(to_drop.item).unwrap().name;
             if entity == *player_entity {
-                game_log.add(format!("You dropped the {}", name));
+                game_log.add(format!("You dropped the {}", magic_item_names.display_name(name)));
             }
         }
 
@@ -0,0 +1,176 @@
+extern crate specs;
+
+use rltk::{ColorPair, LineAlg, Point, RGB};
+use specs::prelude::*;
+
+use crate::{AttackOutcome, CombatStats, Cursed, DamageKind, Equipped, GameLog, IMPACT_VOLUME, InBackpack, IsVisible, MagicItemNames, Map, Name, NoiseBuilder, ParticleBuilder, Position, Renderable, roll_attack, SpawnBuilder, Stackable, SuffersDamage, TakesTurn, ThrowDamage, WantsToThrow, Weight};
+
+pub const THROW_RANGE: i32 = 6;
+const THROW_TIME_COST: u32 = 100;
+/// Items without their own `ThrowDamage` hit harder the heavier they are.
+const WEIGHT_PER_DAMAGE_BONUS: f32 = 5.;
+
+pub struct ItemThrowSystem;
+
+impl ItemThrowSystem {
+    pub const NAME: &'static str = "throw";
+}
+
+impl<'a> System<'a> for ItemThrowSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteStorage<'a, WantsToThrow>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, SuffersDamage>,
+        WriteStorage<'a, TakesTurn>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, ThrowDamage>,
+        ReadStorage<'a, Renderable>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, IsVisible>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, Stackable>,
+        WriteExpect<'a, SpawnBuilder>,
+        ReadStorage<'a, Weight>,
+        ReadExpect<'a, MagicItemNames>,
+        ReadStorage<'a, Cursed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            mut game_log,
+            mut particle_builder,
+            mut noise_builder,
+            mut wants_to_throw,
+            mut positions,
+            mut in_backpacks,
+            mut suffers_damage,
+            mut takes_turn,
+            combat_stats,
+            throw_damage,
+            renderables,
+            names,
+            is_visible,
+            mut equipped,
+            mut stackables,
+            mut spawn_builder,
+            weights,
+            magic_item_names,
+            cursed,
+        ) = data;
+
+        for (entity, to_throw) in (&entities, &wants_to_throw).join() {
+            let start = match positions.get(entity) {
+                None => continue,
+                Some(position) => Point::new(position.x, position.y),
+            };
+
+            if equipped.contains(to_throw.item) && cursed.contains(to_throw.item) {
+                if is_visible.get(entity).is_some() {
+                    let item_name = names.get(to_throw.item).map_or("something", |name| &name.name);
+                    game_log.add(format!("The {} is cursed, and will not leave the thrower's hands!", item_name));
+                }
+                continue;
+            }
+
+            if let Some(takes_turn) = takes_turn.get_mut(entity) {
+                takes_turn.time_score += THROW_TIME_COST;
+            }
+
+            let (path, victim) = trace_flight(&map, &combat_stats, start, to_throw.target);
+            let landing = *path.last().unwrap_or(&start);
+
+            let item_name = names.get(to_throw.item).map_or("something", |name| &name.name);
+            let item_display_name = magic_item_names.display_name(item_name);
+
+            if Stackable::split_one(&mut stackables, to_throw.item) {
+                spawn_builder.request_split(landing, to_throw.item);
+            } else {
+                in_backpacks.remove(to_throw.item);
+                equipped.remove(to_throw.item);
+                positions
+                    .insert(to_throw.item, Position { x: landing.x, y: landing.y })
+                    .expect("Unable to insert position");
+            }
+
+            noise_builder.request_noise(landing, IMPACT_VOLUME, Some(entity));
+
+            if let Some(renderable) = renderables.get(to_throw.item) {
+                particle_builder.request_projectile(&path, ColorPair::new(renderable.fg, RGB::named(rltk::BLACK)), renderable.glyph);
+            }
+
+            let thrower_name = names.get(entity).map_or("Something", |name| &name.name);
+            let is_witnessed = is_visible.get(entity).is_some() || victim.is_some_and(|victim| is_visible.get(victim).is_some());
+
+            if is_witnessed {
+                game_log.add(format!("{} throws the {}.", thrower_name, item_display_name));
+            }
+
+            let victim = match victim {
+                None => continue,
+                Some(victim) => victim,
+            };
+
+            let attack_bonus = combat_stats.get(entity).map_or(0, |stats| stats.power);
+            let defense = combat_stats.get(victim).map_or(0, |stats| stats.defense);
+            let damage = match throw_damage.get(to_throw.item) {
+                Some(throw_damage) => throw_damage.damage,
+                None => {
+                    let weight_bonus = weights.get(to_throw.item).map_or(0, |weight| (weight.weight / WEIGHT_PER_DAMAGE_BONUS) as i32);
+                    rltk::DiceType::new(1, 2, weight_bonus)
+                }
+            };
+
+            let outcome = roll_attack(attack_bonus, defense, damage);
+
+            if is_witnessed {
+                let victim_name = names.get(victim).map_or("something", |name| &name.name);
+                game_log.add(outcome.describe(&format!("The {}", item_display_name), victim_name));
+            }
+
+            match outcome {
+                AttackOutcome::Hit(damage) | AttackOutcome::Critical(damage) => {
+                    SuffersDamage::new_damage(&mut suffers_damage, victim, damage, DamageKind::Physical, Some(entity));
+                }
+                AttackOutcome::Fumble | AttackOutcome::Miss => {}
+            }
+        }
+
+        wants_to_throw.clear();
+    }
+}
+
+/// Follows the line from `start` towards `target`, stopping before walls and closed doors and on the first living creature.
+pub fn trace_flight(map: &Map, combat_stats: &ReadStorage<CombatStats>, start: Point, target: Point) -> (Vec<Point>, Option<Entity>) {
+    let mut path = Vec::new();
+
+    for point in rltk::line2d(LineAlg::Bresenham, start, target).into_iter().skip(1) {
+        if !map.is_valid(point.x, point.y) {
+            break;
+        }
+
+        let idx = map.xy_idx(point.x, point.y);
+        if !map.is_walkable_idx(idx) || map.view_blocked[idx] {
+            break;
+        }
+
+        path.push(point);
+
+        let victim = map.tile_content[idx]
+            .iter()
+            .find(|other| combat_stats.get(**other).is_some_and(|stats| stats.hp > 0));
+
+        if let Some(victim) = victim {
+            return (path, Some(*victim));
+        }
+    }
+
+    (path, None)
+}
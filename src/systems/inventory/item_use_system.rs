@@ -5,7 +5,10 @@ use std::cmp::min;
 use rltk::{Algorithm2D, ColorPair, Point, RGB};
 use specs::prelude::*;
 
-use crate::{AreaOfEffect, CombatStats, Confusion, Consumable, GameLog, InflictsDamage, LONG_LIFETIME, Map, MEDIUM_LIFETIME, Name, ParticleBuilder, Position, ProvidesHealing, SuffersDamage, WantsToUseItem};
+use crate::{Ally, AreaOfEffect, Charges, Charms, CombatStats, Confusion, Consumable, Cursed, DetectsMonsters, Enchantment, Equipped, EXPLOSION_VOLUME, Faction, FactionKind, GameLog, HUNGER_STATE_DURATION, HungerClock, HungerState, Identifies, InBackpack, InflictsDamage, is_hostile, Knockback, LONG_LIFETIME, MagicItemNames, MagicMapping, Map, ModifiersKnown, MEDIUM_LIFETIME, Monster, Name, NoiseBuilder, ParticleBuilder, Position, ProvidesFood, ProvidesHealing, push_direction, RangedWeapon, Recharges, RemovesCurse, SpawnBuilder, SPELL_VOLUME, Stackable, SuffersDamage, SummonsAlly, Telepathy, Teleports, WantsToBePushed, WantsToTeleport, WantsToUseItem, with_modifiers};
+
+/// An explosion lights up this many tiles beyond its blast.
+const EXPLOSION_GLARE: i32 = 2;
 
 pub struct ItemUseSystem;
 
@@ -13,44 +16,82 @@ impl<'a> System<'a> for ItemUseSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
-        ReadExpect<'a, Map>,
+        WriteExpect<'a, Map>,
         Entities<'a>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, ProvidesHealing>,
         WriteStorage<'a, WantsToUseItem>,
         WriteStorage<'a, CombatStats>,
-        ReadStorage<'a, Consumable>,
+        (ReadStorage<'a, Consumable>, ReadStorage<'a, Charms>),
         ReadStorage<'a, InflictsDamage>,
         WriteStorage<'a, SuffersDamage>,
         ReadStorage<'a, AreaOfEffect>,
         WriteStorage<'a, Confusion>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        (ReadStorage<'a, SummonsAlly>, ReadStorage<'a, MagicMapping>, ReadStorage<'a, Teleports>, WriteStorage<'a, WantsToTeleport>, ReadStorage<'a, DetectsMonsters>, WriteStorage<'a, Telepathy>),
+        WriteStorage<'a, Faction>,
+        WriteStorage<'a, Ally>,
+        WriteStorage<'a, Monster>,
+        WriteExpect<'a, SpawnBuilder>,
+        WriteExpect<'a, NoiseBuilder>,
+        (ReadStorage<'a, RangedWeapon>, WriteStorage<'a, Equipped>, WriteStorage<'a, Cursed>, ReadStorage<'a, RemovesCurse>),
+        (ReadStorage<'a, Knockback>, WriteStorage<'a, WantsToBePushed>),
+        (ReadStorage<'a, ProvidesFood>, WriteStorage<'a, HungerClock>),
+        (WriteStorage<'a, Stackable>, WriteStorage<'a, Charges>, ReadStorage<'a, Recharges>),
+        (WriteExpect<'a, MagicItemNames>, ReadStorage<'a, Identifies>, ReadStorage<'a, InBackpack>, WriteStorage<'a, ModifiersKnown>, ReadStorage<'a, Enchantment>),
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             player_entity,
             mut game_log,
-            map,
+            mut map,
             entities,
             names,
             provides_healing,
             mut wants_to_use_items,
             mut combat_stats,
-            consumables,
+            (consumables, charms),
             inflicts_damage,
             mut suffers_damage,
             aoe,
             mut confusion,
             mut particle_builder,
             positions,
+            (summons_ally, magic_mappings, teleports, mut wants_to_teleport, detects_monsters, mut telepathies),
+            mut factions,
+            mut allies,
+            mut monsters,
+            mut spawn_builder,
+            mut noise_builder,
+            (ranged_weapons, mut equipped, mut cursed, removes_curse),
+            (knockbacks, mut wants_to_be_pushed),
+            (provides_food, mut hunger_clocks),
+            (mut stackables, mut charges, recharges),
+            (mut magic_item_names, identifies, in_backpacks, mut modifiers_known, enchantments),
         ) = data;
 
         for (user_entity, use_item) in (&entities, &wants_to_use_items).join() {
             let mut used_item = false;
             let item_entity = use_item.item;
 
+            if charges.get(item_entity).is_some_and(|charges| charges.current <= 0) {
+                if user_entity == *player_entity {
+                    game_log.add("You zap it, but nothing happens.".to_string());
+                }
+                continue;
+            }
+
+            if user_entity == *player_entity {
+                if let Some(name) = names.get(item_entity) {
+                    let appearance = magic_item_names.display_name(&name.name);
+                    if magic_item_names.identify(&name.name) {
+                        game_log.add(format!("The {} was a {}.", appearance, name.name));
+                    }
+                }
+            }
+
             let mut targets: Vec<Entity> = Vec::new();
             match use_item.target {
                 None => targets.push(*player_entity),
@@ -58,12 +99,17 @@ impl<'a> System<'a> for ItemUseSystem {
                     let area_of_effect = aoe.get(use_item.item);
                     match area_of_effect {
                         None => {
+                            noise_builder.request_noise(target, SPELL_VOLUME, Some(user_entity));
+
                             let target_idx = map.point2d_to_index(target) as usize;
                             let mut hit_entities = map.tile_content[target_idx].to_vec();
 
                             targets.append(&mut hit_entities);
                         }
                         Some(area_of_effect) => {
+                            noise_builder.request_noise(target, EXPLOSION_VOLUME, Some(user_entity));
+                            spawn_builder.request_flash(target, area_of_effect.radius + EXPLOSION_GLARE, RGB::named(rltk::ORANGE));
+
                             let blast_tiles = rltk::field_of_view(
                                 target,
                                 area_of_effect.radius,
@@ -126,16 +172,25 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            if provides_food.get(item_entity).is_some() {
+                for target in targets.iter() {
+                    if let Some(hunger_clock) = hunger_clocks.get_mut(*target) {
+                        used_item = true;
+                        hunger_clock.state = HungerState::WellFed;
+                        hunger_clock.duration = HUNGER_STATE_DURATION;
+
+                        if *target == *player_entity {
+                            let item_name = &names.get(item_entity).unwrap().name;
+                            game_log.add(format!("You eat the {}.", item_name));
+                        }
+                    }
+                }
+            }
+
             let damage_item = inflicts_damage.get(item_entity);
             if let Some(damage_item) = damage_item {
                 for target in stat_targets.iter() {
-                    suffers_damage
-                        .insert(
-                            **target,
-                            SuffersDamage {
-                                amount: damage_item.damage
-                            })
-                        .expect("Unable to insert");
+                    SuffersDamage::new_damage(&mut suffers_damage, **target, damage_item.damage, damage_item.kind, Some(user_entity));
 
                     used_item = true;
 
@@ -147,6 +202,21 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            if let (Some(knockback), Some(user_position)) = (knockbacks.get(item_entity), positions.get(user_entity)) {
+                let user_position = Point::new(user_position.x, user_position.y);
+
+                for target in stat_targets.iter() {
+                    if let Some(target_position) = positions.get(**target) {
+                        used_item = true;
+
+                        let direction = push_direction(user_position, Point::new(target_position.x, target_position.y));
+                        wants_to_be_pushed
+                            .insert(**target, WantsToBePushed { direction, distance: knockback.distance, source: user_entity })
+                            .expect("Unable to insert push");
+                    }
+                }
+            }
+
             let mut mobs_to_confuse = Vec::new();
 
             let confusion_item = confusion.get(item_entity);
@@ -176,13 +246,210 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            if summons_ally.get(item_entity).is_some() {
+                if let Some(position) = positions.get(user_entity) {
+                    used_item = true;
+                    spawn_builder.request_ally(Point::new(position.x, position.y));
+
+                    if user_entity == *player_entity {
+                        let item_name = &names.get(item_entity).unwrap().name;
+                        game_log.add(format!("You read {}, and something answers the call.", item_name));
+                    }
+                }
+            }
+
+            if magic_mappings.get(item_entity).is_some() {
+                used_item = true;
+
+                for revealed in map.revealed_tiles.iter_mut() {
+                    *revealed = true;
+                }
+
+                if user_entity == *player_entity {
+                    game_log.add("The layout of the dungeon unfolds in your mind.".to_string());
+                }
+            }
+
+            if teleports.get(item_entity).is_some() {
+                if let Some(destination) = map.random_free_tile() {
+                    used_item = true;
+                    wants_to_teleport
+                        .insert(user_entity, WantsToTeleport { destination })
+                        .expect("Unable to insert teleport");
+
+                    if user_entity == *player_entity {
+                        game_log.add("The world blurs around you.".to_string());
+                    }
+                }
+            }
+
+            if let Some(detects_monsters) = detects_monsters.get(item_entity) {
+                used_item = true;
+                telepathies
+                    .insert(user_entity, Telepathy { turns: detects_monsters.turns })
+                    .expect("Unable to insert telepathy");
+
+                if user_entity == *player_entity {
+                    game_log.add("You sense the presence of monsters.".to_string());
+                }
+            }
+
+            if charms.get(item_entity).is_some() {
+                for target in stat_targets.iter() {
+                    let is_charmable = monsters.get(**target).is_some()
+                        && factions.get(**target).is_some_and(|faction| is_hostile(FactionKind::Player, faction.kind));
+                    if !is_charmable {
+                        continue;
+                    }
+
+                    used_item = true;
+
+                    factions.insert(**target, Faction { kind: FactionKind::Player }).expect("Unable to insert faction");
+                    allies.insert(**target, Ally).expect("Unable to insert ally");
+                    monsters.remove(**target);
+
+                    if user_entity == *player_entity {
+                        let item_name = &names.get(item_entity).unwrap().name;
+                        let mob_name = &names.get(**target).unwrap().name;
+                        game_log.add(format!("You use {} on {}, and they join your side.", item_name, mob_name));
+                    }
+
+                    if let Some(position) = positions.get(**target) {
+                        particle_builder.request_aura(
+                            Point::new(position.x, position.y),
+                            MEDIUM_LIFETIME,
+                            rltk::RGB::named(rltk::GREEN),
+                            rltk::to_cp437('♥'),
+                        );
+                    }
+                }
+            }
+
+            if identifies.get(item_entity).is_some() {
+                used_item = true;
+
+                let carried = (&entities, &names, &in_backpacks)
+                    .join()
+                    .filter(|(_, _, in_backpack)| in_backpack.owner == user_entity)
+                    .map(|(entity, name, _)| (entity, name.name.to_string()))
+                    .collect::<Vec<(Entity, String)>>();
+
+                let mut identified_any = false;
+                for (item, name) in carried.iter() {
+                    let appearance = magic_item_names.display_name(name);
+                    if magic_item_names.identify(name) {
+                        identified_any = true;
+                        if user_entity == *player_entity {
+                            game_log.add(format!("The {} is a {}.", appearance, name));
+                        }
+                    }
+
+                    let has_modifiers = enchantments.get(*item).is_some() || cursed.get(*item).is_some();
+                    if has_modifiers && !modifiers_known.contains(*item) {
+                        identified_any = true;
+                        modifiers_known.insert(*item, ModifiersKnown).expect("Unable to insert known modifiers");
+                        if user_entity == *player_entity {
+                            let known_name = with_modifiers(name.to_string(), enchantments.get(*item), cursed.get(*item).is_some());
+                            game_log.add(format!("The {} is a {}.", name, known_name));
+                        }
+                    }
+                }
+
+                if !identified_any && user_entity == *player_entity {
+                    game_log.add("You learn nothing new.".to_string());
+                }
+            }
+
+            if recharges.get(item_entity).is_some() {
+                used_item = true;
+
+                let carried = (&entities, &in_backpacks)
+                    .join()
+                    .filter(|(_, in_backpack)| in_backpack.owner == user_entity)
+                    .map(|(entity, _)| entity)
+                    .collect::<Vec<Entity>>();
+
+                for item in carried {
+                    if let Some(item_charges) = charges.get_mut(item) {
+                        item_charges.current = item_charges.max;
+
+                        if user_entity == *player_entity {
+                            let item_name = &names.get(item).unwrap().name;
+                            game_log.add(format!("The {} hums with renewed power.", magic_item_names.display_name(item_name)));
+                        }
+                    }
+                }
+            }
+
+            if removes_curse.get(item_entity).is_some() {
+                used_item = true;
+
+                let carried_cursed = (&entities, &in_backpacks, &cursed)
+                    .join()
+                    .filter(|(_, in_backpack, _)| in_backpack.owner == user_entity)
+                    .map(|(entity, _, _)| entity)
+                    .collect::<Vec<Entity>>();
+
+                for item in carried_cursed.iter() {
+                    cursed.remove(*item);
+
+                    if user_entity == *player_entity && modifiers_known.contains(*item) {
+                        game_log.add(format!("The {} is no longer cursed.", names.get(*item).unwrap().name));
+                    }
+                }
+
+                if user_entity == *player_entity {
+                    game_log.add("You feel as if someone is watching over you.".to_string());
+                }
+            }
+
+            if ranged_weapons.get(item_entity).is_some() {
+                let previously_equipped = (&entities, &ranged_weapons, &equipped)
+                    .join()
+                    .filter(|(_, _, equipped)| equipped.owner == user_entity)
+                    .map(|(entity, _, _)| entity)
+                    .collect::<Vec<Entity>>();
+
+                let item_name = &names.get(item_entity).unwrap().name;
+
+                if let Some(cursed_item) = previously_equipped.iter().find(|previous| cursed.contains(**previous)) {
+                    modifiers_known.insert(*cursed_item, ModifiersKnown).expect("Unable to insert known modifiers");
+                    if user_entity == *player_entity {
+                        game_log.add(format!("You can't let go of the {}, it's cursed!", names.get(*cursed_item).unwrap().name));
+                    }
+                } else {
+                    for previous in previously_equipped.iter() {
+                        equipped.remove(*previous);
+                    }
+
+                    if previously_equipped.contains(&item_entity) {
+                        if user_entity == *player_entity {
+                            game_log.add(format!("You unequip the {}.", item_name));
+                        }
+                    } else {
+                        equipped.insert(item_entity, Equipped { owner: user_entity }).expect("Unable to equip");
+                        modifiers_known.insert(item_entity, ModifiersKnown).expect("Unable to insert known modifiers");
+                        if user_entity == *player_entity {
+                            game_log.add(format!("You equip the {}.", item_name));
+                            if cursed.contains(item_entity) {
+                                game_log.add(format!("The {} is cursed, and will not leave your hands!", item_name));
+                            }
+                        }
+                    }
+                }
+            }
+
             if used_item {
-                if let Some(_consumable) = consumables.get(item_entity) {
-                    entities.delete(item_entity).expect("Delete failed");
+                if let Some(item_charges) = charges.get_mut(item_entity) {
+                    item_charges.current -= 1;
                 }
             }
+
+            if used_item && consumables.get(item_entity).is_some() && !Stackable::split_one(&mut stackables, item_entity) {
+                entities.delete(item_entity).expect("Delete failed");
+            }
         }
 
         wants_to_use_items.clear();
     }
-}
\ No newline at end of file
+}
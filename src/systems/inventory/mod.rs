@@ -1,8 +1,16 @@
+pub use encumbrance::*;
+pub use container_system::*;
 pub use item_collection_system::*;
 pub use item_drop_system::*;
+pub use item_throw_system::*;
 pub use item_use_system::*;
+pub use trade_system::*;
 
+pub mod container_system;
+pub mod encumbrance;
 pub mod item_collection_system;
 pub mod item_drop_system;
+pub mod item_throw_system;
 pub mod item_use_system;
+pub mod trade_system;
 
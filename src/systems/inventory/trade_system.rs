@@ -0,0 +1,160 @@
+extern crate specs;
+
+use specs::prelude::*;
+
+use crate::{backpack_weight, carry_capacity, CombatStats, Cursed, Equipped, GameLog, HARD_CARRY_LIMIT, InBackpack, InContainer, item_weight, MagicItemNames, Name, Price, put_in_backpack, Stackable, Wallet, WantsToBuy, WantsToSell, Weight};
+
+/// Shopkeepers buy items back for this fraction of their price.
+const SELL_PRICE_DIVISOR: i32 = 2;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TradeMode {
+    Buy,
+    Sell,
+}
+
+impl TradeMode {
+    /// What the player pays or receives for the whole stack of an item.
+    pub fn price_of(&self, price: &Price, stackable: Option<&Stackable>) -> i32 {
+        let quantity = stackable.map_or(1, |stackable| stackable.quantity);
+
+        match self {
+            TradeMode::Buy => price.value * quantity,
+            TradeMode::Sell => (price.value / SELL_PRICE_DIVISOR).max(1) * quantity,
+        }
+    }
+}
+
+pub struct TradeSystem;
+
+impl<'a> System<'a> for TradeSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, MagicItemNames>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToBuy>,
+        WriteStorage<'a, WantsToSell>,
+        WriteStorage<'a, Wallet>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        WriteStorage<'a, Equipped>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Price>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, InContainer>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut game_log,
+            magic_item_names,
+            entities,
+            mut wants_to_buy,
+            mut wants_to_sell,
+            mut wallets,
+            mut in_backpacks,
+            mut stackables,
+            mut equipped,
+            cursed,
+            prices,
+            names,
+            weights,
+            combat_stats,
+            in_containers,
+        ) = data;
+
+        for (buyer, to_buy) in (&entities, &wants_to_buy).join() {
+            let price = match prices.get(to_buy.item) {
+                None => continue,
+                Some(price) => TradeMode::Buy.price_of(price, stackables.get(to_buy.item)),
+            };
+            let item_name = magic_item_names.display_name(&names.get(to_buy.item).unwrap().name);
+
+            if let Some(stats) = combat_stats.get(buyer) {
+                let carried = backpack_weight(&in_backpacks, &in_containers, &weights, &stackables, buyer);
+                let bought = item_weight(&in_containers, &weights, &stackables, to_buy.item);
+
+                if carried + bought > carry_capacity(stats) * HARD_CARRY_LIMIT {
+                    if buyer == *player_entity {
+                        game_log.add(format!("You can't carry the {}, it's too heavy.", item_name));
+                    }
+                    continue;
+                }
+            }
+
+            if !transfer_gold(&mut wallets, buyer, to_buy.seller, price) {
+                if buyer == *player_entity {
+                    game_log.add(format!("You can't afford the {}.", item_name));
+                }
+                continue;
+            }
+
+            put_in_backpack(&entities, &mut in_backpacks, &names, &mut stackables, buyer, to_buy.item);
+
+            if buyer == *player_entity {
+                game_log.add(format!("You buy the {} for {} gold.", item_name, price));
+            }
+        }
+
+        wants_to_buy.clear();
+
+        for (seller, to_sell) in (&entities, &wants_to_sell).join() {
+            let price = match prices.get(to_sell.item) {
+                None => continue,
+                Some(price) => TradeMode::Sell.price_of(price, stackables.get(to_sell.item)),
+            };
+            let item_name = magic_item_names.display_name(&names.get(to_sell.item).unwrap().name);
+
+            if equipped.contains(to_sell.item) && cursed.contains(to_sell.item) {
+                if seller == *player_entity {
+                    game_log.add(format!("You can't let go of the {}, it's cursed!", item_name));
+                }
+                continue;
+            }
+
+            let is_empty = !in_containers.join().any(|in_container| in_container.container == to_sell.item);
+            if !is_empty {
+                if seller == *player_entity {
+                    game_log.add(format!("You need to empty the {} before selling it.", item_name));
+                }
+                continue;
+            }
+
+            if !transfer_gold(&mut wallets, to_sell.buyer, seller, price) {
+                if seller == *player_entity {
+                    game_log.add(format!("The shopkeeper can't afford the {}.", item_name));
+                }
+                continue;
+            }
+
+            equipped.remove(to_sell.item);
+            in_backpacks
+                .insert(to_sell.item, InBackpack { owner: to_sell.buyer })
+                .expect("Unable to insert backpack entry");
+
+            if seller == *player_entity {
+                game_log.add(format!("You sell the {} for {} gold.", item_name, price));
+            }
+        }
+
+        wants_to_sell.clear();
+    }
+}
+
+/// Moves `amount` gold between wallets, returning false and changing nothing if `from` cannot pay.
+fn transfer_gold(wallets: &mut WriteStorage<Wallet>, from: Entity, to: Entity, amount: i32) -> bool {
+    match wallets.get_mut(from) {
+        Some(wallet) if wallet.gold >= amount => wallet.gold -= amount,
+        _ => return false,
+    }
+
+    if let Some(wallet) = wallets.get_mut(to) {
+        wallet.gold += amount;
+    }
+
+    true
+}
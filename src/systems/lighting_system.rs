@@ -0,0 +1,43 @@
+use rltk::{Algorithm2D, field_of_view, Point};
+use specs::prelude::*;
+
+use crate::{LightSource, Map, Position};
+
+/// Adds up the light falling on every tile, fading with distance from each `LightSource`.
+pub struct LightingSystem;
+
+impl LightingSystem {
+    pub const NAME: &'static str = "lighting";
+}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, LightSource>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map,
+            positions,
+            light_sources,
+        ) = data;
+
+        map.clear_light();
+
+        for (position, light_source) in (&positions, &light_sources).join() {
+            let origin = Point::new(position.x, position.y);
+            let mut lit_tiles = field_of_view(origin, light_source.radius, &*map);
+            lit_tiles.retain(|p| map.in_bounds(*p));
+
+            for tile in lit_tiles.iter() {
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(origin, *tile);
+                let intensity = (1.0 - distance / (light_source.radius as f32 + 1.0)).max(0.0);
+
+                let idx = map.point2d_to_index(*tile);
+                map.light[idx] = map.light[idx] + light_source.color * intensity;
+            }
+        }
+    }
+}
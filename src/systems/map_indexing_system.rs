@@ -1,7 +1,7 @@
 use specs::{ReadStorage, System, WriteExpect};
 use specs::prelude::*;
 
-use crate::{BlocksTile, Map, Position};
+use crate::{BlocksTile, BlocksVisibility, Hidden, Map, Position, Trap};
 
 pub struct MapIndexingSystem;
 
@@ -14,6 +14,9 @@ impl<'a> System<'a> for MapIndexingSystem {
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, BlocksVisibility>,
+        ReadStorage<'a, Trap>,
+        ReadStorage<'a, Hidden>,
         Entities<'a>, );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -21,9 +24,14 @@ impl<'a> System<'a> for MapIndexingSystem {
             mut map,
             position,
             blockers,
+            view_blockers,
+            traps,
+            hidden,
             entities, ) = data;
 
         map.populate_blocked();
+        map.clear_view_blocked();
+        map.clear_known_traps();
         map.clear_content_index();
 
         for (entity, position) in (&entities, &position).join() {
@@ -34,6 +42,15 @@ impl<'a> System<'a> for MapIndexingSystem {
                 map.blocked[idx] = true;
             }
 
+            if view_blockers.get(entity).is_some() {
+                map.view_blocked[idx] = true;
+            }
+
+            // Traps that have been found are known to everyone, and monsters steer around them.
+            if traps.get(entity).is_some() && hidden.get(entity).is_none() {
+                map.known_traps[idx] = true;
+            }
+
             map.tile_content[idx].push(entity)
         }
     }
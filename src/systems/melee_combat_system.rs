@@ -1,11 +1,56 @@
 extern crate specs;
 
+use rltk::{DiceType, Point, RGB};
 use specs::prelude::*;
 
-use crate::{CanMelee, CombatStats, GameLog, Name, SuffersDamage, TakesTurn, WantsToMelee};
+use crate::{CanMelee, CombatStats, DamageKind, GameLog, IsVisible, Knockback, LONG_LIFETIME, MEDIUM_LIFETIME, MELEE_VOLUME, Name, NoiseBuilder, ParticleBuilder, Position, push_direction, RNG, SuffersDamage, TakesTurn, WantsToBePushed, WantsToMelee};
 
 pub struct MeleeCombatSystem;
 
+const BASE_ARMOUR_CLASS: i32 = 10;
+const FUMBLE_ROLL: i32 = 1;
+const CRITICAL_ROLL: i32 = 20;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum AttackOutcome {
+    Fumble,
+    Miss,
+    Hit(i32),
+    Critical(i32),
+}
+
+impl AttackOutcome {
+    pub fn describe(&self, attacker: &str, target: &str) -> String {
+        match self {
+            AttackOutcome::Fumble => format!("{} fumbles the attack on {}.", attacker, target),
+            AttackOutcome::Miss => format!("{} misses {}.", attacker, target),
+            AttackOutcome::Hit(damage) => format!("{} hits {} for {} hp.", attacker, target, damage),
+            AttackOutcome::Critical(damage) => format!("{} critically hits {} for {} hp!", attacker, target, damage),
+        }
+    }
+}
+
+/// Rolls a d20 plus `attack_bonus` against the target's armour class, then the damage dice.
+/// A natural 1 always fumbles and a natural 20 always hits, rolling the damage dice twice.
+pub fn roll_attack(attack_bonus: i32, defense: i32, damage: DiceType) -> AttackOutcome {
+    let natural_roll = RNG.roll_die(20);
+
+    if natural_roll == FUMBLE_ROLL {
+        return AttackOutcome::Fumble;
+    }
+
+    if natural_roll == CRITICAL_ROLL {
+        let critical_damage = DiceType::new(damage.n_dice * 2, damage.die_type, damage.bonus);
+        return AttackOutcome::Critical(i32::max(1, RNG.roll(critical_damage)));
+    }
+
+    if natural_roll + attack_bonus < BASE_ARMOUR_CLASS + defense {
+        return AttackOutcome::Miss;
+    }
+
+    AttackOutcome::Hit(i32::max(1, RNG.roll(damage)))
+}
+
 impl<'a> System<'a> for MeleeCombatSystem {
     type SystemData = (
         WriteExpect<'a, GameLog>,
@@ -15,6 +60,13 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteStorage<'a, SuffersDamage>,
         WriteStorage<'a, TakesTurn>,
         ReadStorage<'a, CanMelee>,
+        ReadStorage<'a, IsVisible>,
+        Entities<'a>,
+        WriteExpect<'a, NoiseBuilder>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, ParticleBuilder>,
+        ReadStorage<'a, Knockback>,
+        WriteStorage<'a, WantsToBePushed>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -26,9 +78,16 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut suffers_damage,
             mut takes_turn,
             can_melee,
+            is_visible,
+            entities,
+            mut noise_builder,
+            positions,
+            mut particle_builder,
+            knockbacks,
+            mut wants_to_be_pushed,
         ) = data;
 
-        for (wants_melee, name, stats, mut takes_turn, can_melee) in (&wants_melee, &names, &combat_stats, &mut takes_turn, &can_melee).join() {
+        for (entity, wants_melee, name, stats, mut takes_turn, can_melee) in (&entities, &wants_melee, &names, &combat_stats, &mut takes_turn, &can_melee).join() {
             takes_turn.time_score += can_melee.time_cost;
 
             if stats.hp <= 0 {
@@ -43,23 +102,68 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
             let target_name = names.get(wants_melee.target).unwrap();
 
-            let damage = i32::max(0, stats.power - target_stats.defense);
-
-            if damage == 0 {
-                game_log.add(format!(
-                    "{} is unable to hurt {}.",
-                    &name.name,
-                    &target_name.name));
-            } else {
-                game_log.add(format!(
-                    "{} hits {} for {} hp.",
-                    &name.name,
-                    &target_name.name,
-                    damage));
-
-                suffers_damage
-                    .insert(wants_melee.target, SuffersDamage { amount: damage })
-                    .expect("Unable to do damage");
+            let outcome = roll_attack(stats.power, target_stats.defense, can_melee.damage);
+
+            if let Some(position) = positions.get(entity) {
+                noise_builder.request_noise(Point::new(position.x, position.y), MELEE_VOLUME, Some(entity));
+            }
+
+            let is_witnessed = is_visible.get(entity).is_some() || is_visible.get(wants_melee.target).is_some();
+
+            if is_witnessed {
+                game_log.add(outcome.describe(&name.name, &target_name.name));
+            }
+
+            let target_position = positions
+                .get(wants_melee.target)
+                .map(|position| Point::new(position.x, position.y));
+
+            if let (Some(knockback), Some(attacker_position), Some(target_position)) = (knockbacks.get(entity), positions.get(entity), target_position) {
+                if let AttackOutcome::Hit(_) | AttackOutcome::Critical(_) = outcome {
+                    let direction = push_direction(Point::new(attacker_position.x, attacker_position.y), target_position);
+                    wants_to_be_pushed
+                        .insert(wants_melee.target, WantsToBePushed { direction, distance: knockback.distance, source: entity })
+                        .expect("Unable to insert push");
+                }
+            }
+
+            match outcome {
+                AttackOutcome::Fumble => {
+                    takes_turn.time_score += can_melee.time_cost / 2;
+
+                    if let Some(position) = positions.get(entity) {
+                        particle_builder.request_aura(
+                            Point::new(position.x, position.y),
+                            MEDIUM_LIFETIME,
+                            RGB::named(rltk::YELLOW),
+                            rltk::to_cp437('↓'),
+                        );
+                    }
+                }
+                AttackOutcome::Miss => {
+                    if let Some(target_position) = target_position {
+                        particle_builder.request_aura(
+                            target_position,
+                            MEDIUM_LIFETIME,
+                            RGB::named(rltk::GREY70),
+                            rltk::to_cp437('○'),
+                        );
+                    }
+                }
+                AttackOutcome::Hit(damage) => {
+                    SuffersDamage::new_damage(&mut suffers_damage, wants_melee.target, damage, DamageKind::Physical, Some(entity));
+                }
+                AttackOutcome::Critical(damage) => {
+                    SuffersDamage::new_damage(&mut suffers_damage, wants_melee.target, damage, DamageKind::Physical, Some(entity));
+
+                    if let Some(target_position) = target_position {
+                        particle_builder.request_background(
+                            target_position,
+                            LONG_LIFETIME,
+                            RGB::named(rltk::RED),
+                        );
+                    }
+                }
             }
         }
 
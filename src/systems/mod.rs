@@ -1,21 +1,31 @@
 pub use damage_system::*;
+pub use door_system::*;
 pub use global_turn_system::*;
+pub use hearing_system::*;
 pub use inventory::*;
+pub use lighting_system::*;
 pub use map_indexing_system::*;
 pub use melee_combat_system::*;
 pub use monster_ai_system::*;
 pub use movement_system::*;
 pub use particle_system::*;
+pub use ranged_combat_system::*;
+pub use search_system::*;
 pub use visibility_system::*;
 pub use wait_system::*;
 
 pub mod wait_system;
 pub mod damage_system;
+pub mod door_system;
 pub mod visibility_system;
 pub mod monster_ai_system;
 pub mod map_indexing_system;
 pub mod melee_combat_system;
 pub mod inventory;
+pub mod lighting_system;
 pub mod global_turn_system;
 pub mod movement_system;
-pub mod particle_system;
\ No newline at end of file
+pub mod particle_system;
+pub mod hearing_system;
+pub mod ranged_combat_system;
+pub mod search_system;
@@ -4,7 +4,7 @@ extern crate specs;
 use rltk::Point;
 use specs::prelude::*;
 
-use crate::{Confusion, console_log, Map, Monster, Name, Position, RNG, Viewshed, WaitCause, WantsToMelee, WantsToMove, WantsToTakeTurn, WantsToWait};
+use crate::{Ally, Asleep, CombatStats, Confusion, console_log, Door, Faction, Fleeing, Investigating, is_hostile, Map, Name, PackMember, Player, Position, RNG, Viewshed, WaitCause, WantsToMelee, WantsToMove, WantsToOpenDoor, WantsToTakeTurn, WantsToWait};
 
 use self::rltk::Algorithm2D;
 
@@ -18,16 +18,18 @@ impl MonsterAI {
 enum MonsterTurnAction {
     Melee(Entity),
     Move(Point),
+    OpenDoor(Entity),
     Wait { is_confused: bool },
+    Sleep,
 }
 
 impl<'a> System<'a> for MonsterAI {
     type SystemData = (
         WriteExpect<'a, Map>,
         ReadExpect<'a, Point>,
-        ReadExpect<'a, Entity>,
         Entities<'a>,
-        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Ally>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, WantsToTakeTurn>,
@@ -36,15 +38,23 @@ impl<'a> System<'a> for MonsterAI {
         WriteStorage<'a, WantsToWait>,
         ReadStorage<'a, Confusion>,
         ReadStorage<'a, Name>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Asleep>,
+        WriteStorage<'a, Investigating>,
+        ReadStorage<'a, PackMember>,
+        ReadStorage<'a, Fleeing>,
+        ReadStorage<'a, Door>,
+        WriteStorage<'a, WantsToOpenDoor>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
             player_pos,
-            player_entity,
             entities,
-            monster,
+            players,
+            allies,
             positions,
             mut wants_to_melee,
             wants_to_take_turn,
@@ -53,61 +63,155 @@ impl<'a> System<'a> for MonsterAI {
             mut wants_to_wait,
             confusions,
             names,
+            factions,
+            combat_stats,
+            asleep,
+            mut investigating,
+            pack_members,
+            fleeings,
+            doors,
+            mut wants_to_open_door,
         ) = data;
 
         let map = &mut *map;
         let player_pos = *player_pos;
-        let player_idx = map.point2d_to_index(player_pos);
 
-        let get_action = |entity: Entity, position: &Position, viewshed: &Viewshed| -> MonsterTurnAction {
+        // Monsters plan paths through closed doors, opening them when they get there.
+        let closed_doors: Vec<(Entity, usize)> = (&entities, &doors, &positions)
+            .join()
+            .filter(|(_, door, _)| !door.open)
+            .map(|(door, _, position)| (door, map.xy_idx(position.x, position.y)))
+            .collect();
+        let door_idxs: Vec<usize> = closed_doors.iter().map(|(_, idx)| *idx).collect();
+
+        let find_target = |map: &Map, entity: Entity, position: &Position, viewshed: &Viewshed, faction: &Faction| -> Option<(Entity, Point)> {
+            let own_position = Point::new(position.x, position.y);
+
+            viewshed.visible_tiles
+                .iter()
+                .flat_map(|tile| {
+                    let idx = map.point2d_to_index(*tile);
+                    map.tile_content[idx].iter().map(move |other| (*other, *tile))
+                })
+                .filter(|(other, _)| *other != entity)
+                .filter(|(other, _)| combat_stats.get(*other).is_some_and(|stats| stats.hp > 0))
+                .filter(|(other, _)| factions.get(*other).is_some_and(|other_faction| is_hostile(faction.kind, other_faction.kind)))
+                .min_by(|(_, a), (_, b)| {
+                    let a_distance = rltk::DistanceAlg::Pythagoras.distance2d(own_position, *a);
+                    let b_distance = rltk::DistanceAlg::Pythagoras.distance2d(own_position, *b);
+                    a_distance.partial_cmp(&b_distance).unwrap()
+                })
+        };
+
+        let get_action = |map: &mut Map, entity: Entity, position: &Position, viewshed: &Viewshed, faction: &Faction, investigation: Option<Point>, claimed: &mut Vec<usize>| -> MonsterTurnAction {
+            if asleep.get(entity).is_some() {
+                return MonsterTurnAction::Sleep;
+            }
+
             if confusions.get(entity).is_some() {
                 return MonsterTurnAction::Wait { is_confused: true };
             }
 
-            let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(position.x, position.y), player_pos);
+            let own_position = Point::new(position.x, position.y);
+            let target = find_target(map, entity, position, viewshed, faction);
 
-            const IS_ADJACENT_DISTANCE: f32 = 1.01;
+            if fleeings.get(entity).is_some() {
+                let flee_step = target.and_then(|(_, threat_pos)| find_flee_step(map, own_position, threat_pos));
 
-            if distance < IS_ADJACENT_DISTANCE {
-                return MonsterTurnAction::Melee(*player_entity);
-            } else if viewshed.visible_tiles.contains(&player_pos) {
-                let monster_idx = map.xy_idx(position.x, position.y);
-                let path = rltk::a_star_search(monster_idx, player_idx, map);
+                return match flee_step.or_else(|| find_random_step(map, own_position)) {
+                    Some(step) => MonsterTurnAction::Move(step),
+                    None => MonsterTurnAction::Wait { is_confused: false },
+                };
+            }
 
-                const FIRST_STEP_INDEX: usize = 1;
+            let leader_pos = if allies.get(entity).is_some() {
+                Some(player_pos)
+            } else {
+                pack_members
+                    .get(entity)
+                    .and_then(|pack_member| positions.get(pack_member.leader))
+                    .map(|leader_position| Point::new(leader_position.x, leader_position.y))
+            };
 
-                if path.success && path.steps.len() > FIRST_STEP_INDEX {
-                    let first_step_idx = path.steps[FIRST_STEP_INDEX];
-                    let first_step = map.index_to_point2d(first_step_idx);
-                    return MonsterTurnAction::Move(first_step);
-                } else {
-                    return MonsterTurnAction::Wait { is_confused: false };
+            if let Some((target, target_pos)) = target {
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(own_position, target_pos);
+
+                const IS_ADJACENT_DISTANCE: f32 = 1.01;
+
+                if distance < IS_ADJACENT_DISTANCE {
+                    return MonsterTurnAction::Melee(target);
                 }
-            } else {
-                let delta: (i32, i32);
-
-                match RNG.roll_die(4) {
-                    1 => delta = (1, 0),
-                    2 => delta = (-1, 0),
-                    3 => delta = (0, 1),
-                    4 => delta = (0, -1),
-                    _ => delta = (0, 0),
+
+                let mut destination = target_pos;
+                if pack_members.get(entity).is_some() {
+                    if let Some(surround_spot) = find_surround_spot(map, own_position, target_pos, claimed) {
+                        claimed.push(map.point2d_to_index(surround_spot));
+                        destination = surround_spot;
+                    }
+                }
+
+                match next_step_towards(map, position, destination, &door_idxs) {
+                    Some(first_step) => MonsterTurnAction::Move(first_step),
+                    None => MonsterTurnAction::Wait { is_confused: false },
                 }
+            } else if let Some(first_step) = investigation.and_then(|destination| next_step_towards(map, position, destination, &door_idxs)) {
+                MonsterTurnAction::Move(first_step)
+            } else if let Some(leader_pos) = leader_pos {
+                const FOLLOW_DISTANCE: f32 = 2.5;
 
-                let (delta_x, delta_y) = delta;
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(own_position, leader_pos);
 
-                let next_step = Point::new(position.x + delta_x, position.y + delta_y);
+                if distance >= FOLLOW_DISTANCE {
+                    return match next_step_towards(map, position, leader_pos, &door_idxs) {
+                        Some(first_step) => MonsterTurnAction::Move(first_step),
+                        None => MonsterTurnAction::Wait { is_confused: false },
+                    };
+                }
 
-                if RNG.roll_die(7) > 1 {
-                    return MonsterTurnAction::Move(next_step);
-                } else {
+                if allies.get(entity).is_some() {
                     return MonsterTurnAction::Wait { is_confused: false };
                 }
+
+                let formation_step = find_random_step(map, own_position)
+                    .filter(|step| rltk::DistanceAlg::Pythagoras.distance2d(*step, leader_pos) < FOLLOW_DISTANCE);
+
+                match formation_step {
+                    Some(step) => MonsterTurnAction::Move(step),
+                    None => MonsterTurnAction::Wait { is_confused: false },
+                }
+            } else {
+                match find_random_step(map, own_position) {
+                    Some(step) => MonsterTurnAction::Move(step),
+                    None => MonsterTurnAction::Wait { is_confused: false },
+                }
             }
         };
 
-        for (entity, _monster, position, _turn, viewshed, name) in (&entities, &monster, &positions, &wants_to_take_turn, &viewsheds, &names).join() {
-            let action = get_action(entity, position, viewshed);
+        let mut claimed: Vec<usize> = Vec::new();
+
+        for (entity, _not_player, position, _turn, viewshed, name, faction) in (&entities, !&players, &positions, &wants_to_take_turn, &viewsheds, &names, &factions).join() {
+            let mut investigation = investigating.get(entity).map(|investigating| investigating.destination);
+
+            if let Some(destination) = investigation {
+                const ARRIVED_DISTANCE: f32 = 1.5;
+
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(position.x, position.y), destination);
+                if distance < ARRIVED_DISTANCE {
+                    investigating.remove(entity);
+                    investigation = None;
+                }
+            }
+
+            let action = match get_action(map, entity, position, viewshed, faction, investigation, &mut claimed) {
+                MonsterTurnAction::Move(step) => {
+                    let step_idx = map.point2d_to_index(step);
+                    match closed_doors.iter().find(|(_, idx)| *idx == step_idx) {
+                        Some((door, _)) => MonsterTurnAction::OpenDoor(*door),
+                        None => MonsterTurnAction::Move(step),
+                    }
+                }
+                action => action,
+            };
 
             console_log(format!("           {}: {:?}", name.name, action));
 
@@ -118,6 +222,9 @@ impl<'a> System<'a> for MonsterAI {
                 MonsterTurnAction::Move(destination) => {
                     wants_to_move.insert(entity, WantsToMove { destination }).expect("Unable to insert intent");
                 }
+                MonsterTurnAction::OpenDoor(door) => {
+                    wants_to_open_door.insert(entity, WantsToOpenDoor { door }).expect("Unable to insert intent");
+                }
                 MonsterTurnAction::Wait { is_confused } => {
                     let cause = match is_confused {
                         true => WaitCause::Confusion,
@@ -126,7 +233,100 @@ impl<'a> System<'a> for MonsterAI {
 
                     wants_to_wait.insert(entity, WantsToWait { cause }).expect("Unable to insert intent");
                 }
+                MonsterTurnAction::Sleep => {
+                    wants_to_wait.insert(entity, WantsToWait { cause: WaitCause::Sleep }).expect("Unable to insert intent");
+                }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// The first step of the shortest path to `destination`, treating the `passable` tiles as free.
+fn next_step_towards(map: &mut Map, position: &Position, destination: Point, passable: &[usize]) -> Option<Point> {
+    let start_idx = map.xy_idx(position.x, position.y);
+    let destination_idx = map.point2d_to_index(destination);
+
+    let overrides: Vec<(usize, bool)> = passable
+        .iter()
+        .map(|idx| (*idx, false))
+        // The destination is blocked when a BlocksTile entity stands on it, which would make it unreachable.
+        .chain(std::iter::once((destination_idx, false)))
+        .collect();
+    let was_blocked: Vec<bool> = overrides.iter().map(|(idx, _)| map.blocked[*idx]).collect();
+    for (idx, is_blocked) in overrides.iter() {
+        map.blocked[*idx] = *is_blocked;
+    }
+
+    let path = rltk::a_star_search(start_idx, destination_idx, map);
+
+    for ((idx, _), was_blocked) in overrides.iter().zip(was_blocked.iter()).rev() {
+        map.blocked[*idx] = *was_blocked;
+    }
+
+    const FIRST_STEP_INDEX: usize = 1;
+
+    if path.success && path.steps.len() > FIRST_STEP_INDEX {
+        Some(map.index_to_point2d(path.steps[FIRST_STEP_INDEX]))
+    } else {
+        None
+    }
+}
+
+/// Picks a random cardinal step, or `None` when the monster should idle instead. Wandering never leads onto a hazard.
+fn find_random_step(map: &Map, position: Point) -> Option<Point> {
+    let delta: (i32, i32);
+
+    match RNG.roll_die(4) {
+        1 => delta = (1, 0),
+        2 => delta = (-1, 0),
+        3 => delta = (0, 1),
+        4 => delta = (0, -1),
+        _ => delta = (0, 0),
+    }
+
+    let (delta_x, delta_y) = delta;
+
+    let step = Point::new(position.x + delta_x, position.y + delta_y);
+
+    if RNG.roll_die(7) > 1 && !is_hazard_at(map, step) {
+        Some(step)
+    } else {
+        None
+    }
+}
+
+/// Pathfinding only crosses hazards when there is no other way, but no other kind of step should ever enter one.
+fn is_hazard_at(map: &Map, point: Point) -> bool {
+    map.is_valid(point.x, point.y) && map.is_hazardous_idx(map.point2d_to_index(point))
+}
+
+fn get_open_neighbours(map: &Map, position: Point) -> Vec<Point> {
+    [(0, -1), (0, 1), (1, 0), (-1, 0)]
+        .iter()
+        .map(|(delta_x, delta_y)| Point::new(position.x + delta_x, position.y + delta_y))
+        .filter(|point| map.is_valid(point.x, point.y) && !map.is_blocked(point.x, point.y) && !is_hazard_at(map, *point))
+        .collect()
+}
+
+/// Finds the closest free tile next to `target` that no other pack member is already heading for.
+fn find_surround_spot(map: &Map, position: Point, target: Point, claimed: &[usize]) -> Option<Point> {
+    get_open_neighbours(map, target)
+        .into_iter()
+        .filter(|spot| !claimed.contains(&map.point2d_to_index(*spot)))
+        .min_by(|a, b| {
+            let a_distance = rltk::DistanceAlg::Pythagoras.distance2d(position, *a);
+            let b_distance = rltk::DistanceAlg::Pythagoras.distance2d(position, *b);
+            a_distance.partial_cmp(&b_distance).unwrap()
+        })
+}
+
+fn find_flee_step(map: &Map, position: Point, threat: Point) -> Option<Point> {
+    let current_distance = rltk::DistanceAlg::Pythagoras.distance2d(position, threat);
+
+    get_open_neighbours(map, position)
+        .into_iter()
+        .map(|step| (step, rltk::DistanceAlg::Pythagoras.distance2d(step, threat)))
+        .filter(|(_, distance)| *distance > current_distance)
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(step, _)| step)
+}
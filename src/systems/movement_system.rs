@@ -1,9 +1,14 @@
 extern crate specs;
 
-use rltk::{Point, RGB};
+use rltk::{DiceType, Point, RGB};
 use specs::prelude::*;
 
-use crate::{BlocksTile, CanMove, console_log, Map, ParticleBuilder, Player, Position, SHORT_LIFETIME, TakesTurn, Viewshed, WantsToMove};
+use crate::{backpack_weight, BlocksTile, CanMove, CombatStats, Confusion, console_log, DamageKind, effective_move_cost, FOOTSTEP_VOLUME, GameLog, Hidden, InBackpack, InContainer, IsVisible, Map, MEDIUM_LIFETIME, MELEE_VOLUME, Name, NoiseBuilder, ParticleBuilder, Player, Position, RNG, SHORT_LIFETIME, Stackable, SuffersDamage, TakesTurn, Trap, TrapKind, Viewshed, WantsToBePushed, WantsToMove, WantsToTeleport, Weight};
+
+const COLLISION_DAMAGE: DiceType = DiceType { n_dice: 1, die_type: 4, bonus: 0 };
+const DART_DAMAGE: DiceType = DiceType { n_dice: 1, die_type: 4, bonus: 1 };
+const ALARM_VOLUME: i32 = 30;
+const CONFUSION_GAS_TURNS: i32 = 4;
 
 pub struct MovementSystem;
 
@@ -24,6 +29,21 @@ impl<'a> System<'a> for MovementSystem {
         ReadStorage<'a, CanMove>,
         ReadStorage<'a, BlocksTile>,
         WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteStorage<'a, WantsToBePushed>,
+        WriteStorage<'a, SuffersDamage>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, IsVisible>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, Stackable>,
+        ReadStorage<'a, CombatStats>,
+        WriteStorage<'a, WantsToTeleport>,
+        ReadStorage<'a, Trap>,
+        WriteStorage<'a, Hidden>,
+        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, InContainer>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -39,10 +59,30 @@ impl<'a> System<'a> for MovementSystem {
             can_move,
             blocks_tiles,
             mut particle_builder,
+            mut noise_builder,
+            mut wants_to_be_pushed,
+            mut suffers_damage,
+            mut game_log,
+            names,
+            is_visible,
+            in_backpacks,
+            weights,
+            stackables,
+            combat_stats,
+            mut wants_to_teleport,
+            traps,
+            mut hidden,
+            mut confusions,
+            in_containers,
         ) = data;
 
+        let mut entered_tiles: Vec<(Entity, Point)> = Vec::new();
+        let mut sprung_traps: Vec<(Entity, Entity, Point)> = Vec::new();
+
         for (entity, wants_to_move, mut takes_turn, mut position, can_move) in (&entities, &wants_to_move, &mut takes_turn, &mut positions, &can_move).join() {
-            takes_turn.time_score += can_move.time_cost;
+            let carried = backpack_weight(&in_backpacks, &in_containers, &weights, &stackables, entity);
+            let move_cost = effective_move_cost(can_move, combat_stats.get(entity), carried);
+            takes_turn.time_score += move_cost;
 
             let new_position = wants_to_move.destination;
 
@@ -67,6 +107,8 @@ impl<'a> System<'a> for MovementSystem {
                 RGB::named(rltk::GREY50),
             );
 
+            noise_builder.request_noise(new_position, FOOTSTEP_VOLUME, Some(entity));
+
             let is_blocker = blocks_tiles.get(entity).is_some();
             if is_blocker {
                 map.blocked[old_position_idx as usize] = false;
@@ -78,8 +120,184 @@ impl<'a> System<'a> for MovementSystem {
                 player_position.x = new_position.x;
                 player_position.y = new_position.y;
             }
+
+            let tile = map.tiles[new_position_idx];
+            takes_turn.time_score += (move_cost as f32 * (tile.movement_cost() - 1.0)) as u32;
+
+            entered_tiles.push((entity, new_position));
         }
 
         wants_to_move.clear();
+
+        for (entity, push, position) in (&entities, &wants_to_be_pushed, &mut positions).join() {
+            // Something pushed from its own tile, such as by a self-targeted Force Bolt, has nowhere to go.
+            if push.direction == Point::new(0, 0) {
+                continue;
+            }
+
+            let start_idx = map.xy_idx(position.x, position.y);
+            let mut collision: Option<Option<Entity>> = None;
+
+            for _ in 0..push.distance {
+                let next = Point::new(position.x + push.direction.x, position.y + push.direction.y);
+
+                if !map.is_valid(next.x, next.y) || !map.is_walkable_idx(map.xy_idx(next.x, next.y)) {
+                    collision = Some(None);
+                    break;
+                }
+
+                if map.is_blocked(next.x, next.y) {
+                    let next_idx = map.xy_idx(next.x, next.y);
+                    let obstacle = map.tile_content[next_idx]
+                        .iter()
+                        .find(|other| blocks_tiles.get(**other).is_some())
+                        .copied();
+                    collision = Some(obstacle);
+                    break;
+                }
+
+                particle_builder.request_background(
+                    Point::new(position.x, position.y),
+                    SHORT_LIFETIME,
+                    RGB::named(rltk::GREY50),
+                );
+
+                position.x = next.x;
+                position.y = next.y;
+                entered_tiles.push((entity, next));
+            }
+
+            let end_idx = map.xy_idx(position.x, position.y);
+            if end_idx != start_idx {
+                if blocks_tiles.get(entity).is_some() {
+                    map.blocked[start_idx] = false;
+                    map.blocked[end_idx] = true;
+                }
+
+                if let Some(viewshed) = viewsheds.get_mut(entity) {
+                    viewshed.dirty = true;
+                }
+
+                if players.get(entity).is_some() {
+                    player_position.x = position.x;
+                    player_position.y = position.y;
+                }
+            }
+
+            let obstacle = match collision {
+                None => continue,
+                Some(obstacle) => obstacle,
+            };
+
+            let collision_damage = RNG.roll(COLLISION_DAMAGE);
+            SuffersDamage::new_damage(&mut suffers_damage, entity, collision_damage, DamageKind::Physical, Some(push.source));
+            if let Some(obstacle) = obstacle {
+                SuffersDamage::new_damage(&mut suffers_damage, obstacle, collision_damage, DamageKind::Physical, Some(push.source));
+            }
+
+            noise_builder.request_noise(Point::new(position.x, position.y), MELEE_VOLUME, None);
+
+            if is_visible.get(entity).is_some() {
+                let name = names.get(entity).map_or("Something", |name| &name.name);
+                let obstacle_name = obstacle
+                    .and_then(|obstacle| names.get(obstacle))
+                    .map_or("the wall", |name| &name.name);
+                game_log.add(format!("{} slams into {} for {} hp.", name, obstacle_name, collision_damage));
+            }
+        }
+
+        wants_to_be_pushed.clear();
+
+        // Lava and traps affect whatever ends up on them, whether it walked there or was knocked there.
+        for (entity, tile_position) in entered_tiles {
+            let tile_idx = map.xy_idx(tile_position.x, tile_position.y);
+
+            if let Some((damage_dice, damage_kind)) = map.tiles[tile_idx].damage_on_enter() {
+                let damage = RNG.roll(damage_dice);
+                SuffersDamage::new_hazard_damage(&mut suffers_damage, entity, damage, damage_kind, "lava");
+                if map.is_visible(tile_position.x, tile_position.y) {
+                    let name = names.get(entity).map_or("Something", |name| &name.name);
+                    game_log.add(format!("{} ends up in the lava and burns for {} hp.", name, damage));
+                }
+            }
+
+            for trap in map.tile_content[tile_idx].iter().filter(|other| traps.contains(**other)) {
+                sprung_traps.push((entity, *trap, tile_position));
+            }
+        }
+
+        for (victim, trap, trap_position) in sprung_traps {
+            hidden.remove(trap);
+
+            let is_seen = map.is_visible(trap_position.x, trap_position.y);
+            let name = names.get(victim).map_or("Something", |name| &name.name);
+
+            match traps.get(trap).unwrap().kind {
+                TrapKind::Dart => {
+                    let damage = RNG.roll(DART_DAMAGE);
+                    SuffersDamage::new_damage(&mut suffers_damage, victim, damage, DamageKind::Physical, Some(trap));
+                    if is_seen {
+                        game_log.add(format!("A dart shoots out at {} for {} hp.", name, damage));
+                    }
+                }
+                TrapKind::Teleport => {
+                    if let Some(destination) = map.random_free_tile() {
+                        wants_to_teleport
+                            .insert(victim, WantsToTeleport { destination })
+                            .expect("Unable to insert intent");
+                    }
+                    if is_seen {
+                        game_log.add(format!("{} vanishes in a flash of light.", name));
+                    }
+                }
+                TrapKind::Alarm => {
+                    noise_builder.request_noise(trap_position, ALARM_VOLUME, None);
+                    game_log.add("An alarm rings out!".to_string());
+                }
+                TrapKind::ConfusionGas => {
+                    confusions
+                        .insert(victim, Confusion { turns: CONFUSION_GAS_TURNS })
+                        .expect("Unable to insert status");
+                    particle_builder.request_aura(trap_position, MEDIUM_LIFETIME, RGB::named(rltk::MAGENTA), rltk::to_cp437('░'));
+                    if is_seen {
+                        game_log.add(format!("{} is caught in a cloud of confusing gas.", name));
+                    }
+                }
+            }
+        }
+
+        for (entity, teleport, position) in (&entities, &wants_to_teleport, &mut positions).join() {
+            let destination = teleport.destination;
+
+            if map.is_blocked(destination.x, destination.y) {
+                continue;
+            }
+
+            let old_position_idx = map.xy_idx(position.x, position.y);
+            let new_position_idx = map.xy_idx(destination.x, destination.y);
+            position.x = destination.x;
+            position.y = destination.y;
+
+            if blocks_tiles.get(entity).is_some() {
+                map.blocked[old_position_idx] = false;
+                map.blocked[new_position_idx] = true;
+            }
+
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+
+            if players.get(entity).is_some() {
+                player_position.x = destination.x;
+                player_position.y = destination.y;
+            }
+        }
+
+        wants_to_teleport.clear();
     }
 }
+
+/// The single-tile step pointing from `from` towards `to`, used as the direction of a push.
+pub fn push_direction(from: Point, to: Point) -> Point {
+    Point::new((to.x - from.x).signum(), (to.y - from.y).signum())
+}
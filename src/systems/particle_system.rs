@@ -6,6 +6,7 @@ use crate::{Context, ParticleLifetime, Position, Renderable, RenderAura, RenderB
 pub const SHORT_LIFETIME: f32 = 300.;
 pub const MEDIUM_LIFETIME: f32 = 500.;
 pub const LONG_LIFETIME: f32 = 700.;
+const PROJECTILE_STEP_LIFETIME: f32 = 40.;
 
 pub fn cull_dead_particles(ecs: &mut World, context: &mut Context) {
     let mut dead_particles: Vec<Entity> = Vec::new();
@@ -65,6 +66,13 @@ impl ParticleBuilder {
         });
     }
 
+    /// Each tile along `path` lingers a little longer than the previous one, so the trail follows the projectile.
+    pub fn request_projectile(&mut self, path: &[Point], color: ColorPair, glyph: u8) {
+        for (i, position) in path.iter().enumerate() {
+            self.request_entity(*position, PROJECTILE_STEP_LIFETIME * (i + 1) as f32, color, glyph);
+        }
+    }
+
     pub fn request_background(&mut self, position: Point, lifetime: f32, bg: RGB) {
         self.requests.push(ParticleRequest {
             position,
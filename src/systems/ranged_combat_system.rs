@@ -0,0 +1,151 @@
+extern crate specs;
+
+use rltk::{ColorPair, Point, RGB};
+use specs::prelude::*;
+
+use crate::{Ammunition, AttackOutcome, CombatStats, DamageKind, Enchantment, GameLog, IMPACT_VOLUME, InBackpack, IsVisible, Map, Name, NoiseBuilder, ParticleBuilder, Position, RangedWeapon, RNG, roll_attack, SpawnBuilder, Stackable, SuffersDamage, TakesTurn, trace_flight, WantsToFire};
+
+const FIRE_TIME_COST: u32 = 100;
+/// Every this many tiles to the target costs one point of attack bonus.
+const RANGE_PENALTY_STEP: f32 = 3.;
+const AMMO_RECOVERY_CHANCE: i32 = 2;
+
+pub struct RangedCombatSystem;
+
+impl RangedCombatSystem {
+    pub const NAME: &'static str = "ranged_combat";
+}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteExpect<'a, SpawnBuilder>,
+        WriteStorage<'a, WantsToFire>,
+        ReadStorage<'a, Ammunition>,
+        WriteStorage<'a, Stackable>,
+        WriteStorage<'a, SuffersDamage>,
+        WriteStorage<'a, TakesTurn>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, IsVisible>,
+        ReadStorage<'a, Enchantment>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            mut game_log,
+            mut particle_builder,
+            mut noise_builder,
+            mut spawn_builder,
+            mut wants_to_fire,
+            ammunition,
+            mut stackables,
+            mut suffers_damage,
+            mut takes_turn,
+            ranged_weapons,
+            mut in_backpacks,
+            mut positions,
+            combat_stats,
+            names,
+            is_visible,
+            enchantments,
+        ) = data;
+
+        for (entity, to_fire) in (&entities, &wants_to_fire).join() {
+            let start = match positions.get(entity) {
+                None => continue,
+                Some(position) => Point::new(position.x, position.y),
+            };
+
+            let weapon = match ranged_weapons.get(to_fire.weapon) {
+                None => continue,
+                Some(weapon) => weapon,
+            };
+
+            let ammo_entity = (&entities, &ammunition, &in_backpacks)
+                .join()
+                .find(|(_, ammo, in_backpack)| in_backpack.owner == entity && ammo.kind == weapon.ammo)
+                .map(|(ammo_entity, _, _)| ammo_entity);
+
+            let ammo_entity = match ammo_entity {
+                None => {
+                    if is_visible.get(entity).is_some() {
+                        game_log.add(format!("There are no {} left to fire.", weapon.ammo));
+                    }
+                    continue;
+                }
+                Some(ammo_entity) => ammo_entity,
+            };
+
+            if let Some(takes_turn) = takes_turn.get_mut(entity) {
+                takes_turn.time_score += FIRE_TIME_COST;
+            }
+
+            let (path, victim) = trace_flight(&map, &combat_stats, start, to_fire.target);
+            let landing = *path.last().unwrap_or(&start);
+
+            particle_builder.request_projectile(&path, ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)), rltk::to_cp437('*'));
+            noise_builder.request_noise(landing, IMPACT_VOLUME, Some(entity));
+
+            let is_recovered = RNG.roll_die(AMMO_RECOVERY_CHANCE) == 1;
+            if Stackable::split_one(&mut stackables, ammo_entity) {
+                if is_recovered {
+                    spawn_builder.request_split(landing, ammo_entity);
+                }
+            } else if is_recovered {
+                in_backpacks.remove(ammo_entity);
+                positions
+                    .insert(ammo_entity, Position { x: landing.x, y: landing.y })
+                    .expect("Unable to insert position");
+            } else {
+                entities.delete(ammo_entity).expect("Delete failed");
+            }
+
+            let shooter_name = names.get(entity).map_or("Something", |name| &name.name);
+            let weapon_name = names.get(to_fire.weapon).map_or("something", |name| &name.name);
+            let is_witnessed = is_visible.get(entity).is_some() || victim.is_some_and(|victim| is_visible.get(victim).is_some());
+
+            if is_witnessed {
+                game_log.add(format!("{} fires the {}.", shooter_name, weapon_name));
+            }
+
+            let victim = match victim {
+                None => continue,
+                Some(victim) => victim,
+            };
+
+            let range_penalty = (rltk::DistanceAlg::Pythagoras.distance2d(start, landing) / RANGE_PENALTY_STEP) as i32;
+            let enchantment = enchantments.get(to_fire.weapon).map_or(0, |enchantment| enchantment.level);
+            let attack_bonus = combat_stats.get(entity).map_or(0, |stats| stats.power) - range_penalty + enchantment;
+            let defense = combat_stats.get(victim).map_or(0, |stats| stats.defense);
+
+            let mut damage = weapon.damage;
+            damage.bonus += enchantment;
+
+            let outcome = roll_attack(attack_bonus, defense, damage);
+
+            if is_witnessed {
+                let victim_name = names.get(victim).map_or("something", |name| &name.name);
+                game_log.add(outcome.describe(shooter_name, victim_name));
+            }
+
+            match outcome {
+                AttackOutcome::Hit(damage) | AttackOutcome::Critical(damage) => {
+                    SuffersDamage::new_damage(&mut suffers_damage, victim, damage, DamageKind::Physical, Some(entity));
+                }
+                AttackOutcome::Fumble | AttackOutcome::Miss => {}
+            }
+        }
+
+        wants_to_fire.clear();
+    }
+}
@@ -0,0 +1,98 @@
+extern crate specs;
+
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::{GameLog, Hidden, Map, Name, Position, RNG, TakesTurn, Trap, WantsToSearch, WantsToTakeTurn};
+
+const SEARCH_TIME_COST: u32 = 100;
+const SEARCH_RADIUS: f32 = 2.5;
+/// A search finds each nearby trap unless a die of this size rolls a 1.
+const SEARCH_DIE: i32 = 6;
+const PERCEPTION_RADIUS: f32 = 4.0;
+/// Every turn, the player notices each trap in view nearby on a 1 on a die of this size.
+const PERCEPTION_DIE: i32 = 8;
+
+/// Reveals hidden traps, either by searching on purpose or by the player noticing them in passing.
+pub struct SearchSystem;
+
+impl<'a> System<'a> for SearchSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToSearch>,
+        ReadStorage<'a, WantsToTakeTurn>,
+        WriteStorage<'a, TakesTurn>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Trap>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut game_log,
+            map,
+            entities,
+            mut wants_to_search,
+            wants_to_take_turn,
+            mut takes_turn,
+            mut hidden,
+            traps,
+            positions,
+            names,
+        ) = data;
+
+        let hidden_traps_near = |hidden: &WriteStorage<Hidden>, center: Point, radius: f32| -> Vec<Entity> {
+            (&entities, hidden, &traps, &positions)
+                .join()
+                .filter(|(_, _, _, position)| rltk::DistanceAlg::Pythagoras.distance2d(center, Point::new(position.x, position.y)) <= radius)
+                .map(|(trap, _, _, _)| trap)
+                .collect()
+        };
+
+        for (entity, _search, takes_turn, position) in (&entities, &wants_to_search, &mut takes_turn, &positions).join() {
+            takes_turn.time_score += SEARCH_TIME_COST;
+
+            let found: Vec<Entity> = hidden_traps_near(&hidden, Point::new(position.x, position.y), SEARCH_RADIUS)
+                .into_iter()
+                .filter(|_| RNG.roll_die(SEARCH_DIE) > 1)
+                .collect();
+
+            for trap in found.iter() {
+                hidden.remove(*trap);
+            }
+
+            if entity == *player_entity {
+                if found.is_empty() {
+                    game_log.add("You search around but find nothing.".to_string());
+                }
+                for trap in found.iter() {
+                    game_log.add(format!("You find a {}!", names.get(*trap).map_or("trap", |name| &name.name)));
+                }
+            }
+        }
+
+        wants_to_search.clear();
+
+        if !wants_to_take_turn.contains(*player_entity) {
+            return;
+        }
+
+        if let Some(position) = positions.get(*player_entity) {
+            let noticed: Vec<Entity> = hidden_traps_near(&hidden, Point::new(position.x, position.y), PERCEPTION_RADIUS)
+                .into_iter()
+                .filter(|trap| positions.get(*trap).is_some_and(|position| map.is_visible(position.x, position.y)))
+                .filter(|_| RNG.roll_die(PERCEPTION_DIE) == 1)
+                .collect();
+
+            for trap in noticed {
+                hidden.remove(trap);
+                game_log.add(format!("You notice a {}.", names.get(trap).map_or("trap", |name| &name.name)));
+            }
+        }
+    }
+}
@@ -30,25 +30,30 @@ impl<'a> System<'a> for VisibilitySystem {
                 viewshed.visible_tiles.clear();
                 viewshed.visible_tiles = field_of_view(Point::new(position.x, position.y), viewshed.range, &*map);
                 viewshed.visible_tiles.retain(|p| map.in_bounds(*p));
+            }
+
+            // Lights move and go out without the player moving, so the player's view is refreshed every time.
+            let player_entity_or_none = player.get(entity);
+            if let Some(_) = player_entity_or_none {
+                for is_visible in map.visible_tiles.iter_mut() {
+                    *is_visible = false;
+                };
+
+                is_visible.clear();
+
+                for visible_tile in viewshed.visible_tiles.iter() {
+                    let idx = map.xy_idx(visible_tile.x, visible_tile.y);
+                    if !map.is_lit_idx(idx) {
+                        continue;
+                    }
 
-                let player_entity_or_none = player.get(entity);
-                if let Some(_) = player_entity_or_none {
-                    for is_visible in map.visible_tiles.iter_mut() {
-                        *is_visible = false;
-                    };
-
-                    is_visible.clear();
-
-                    for visible_tile in viewshed.visible_tiles.iter() {
-                        let idx = map.xy_idx(visible_tile.x, visible_tile.y);
-                        map.revealed_tiles[idx] = true;
-                        map.visible_tiles[idx] = true;
-                        for tile_entity in map.tile_content[idx].iter() {
-                            is_visible.insert(*tile_entity, IsVisible).expect("Unable to insert");
-                        }
+                    map.revealed_tiles[idx] = true;
+                    map.visible_tiles[idx] = true;
+                    for tile_entity in map.tile_content[idx].iter() {
+                        is_visible.insert(*tile_entity, IsVisible).expect("Unable to insert");
                     }
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
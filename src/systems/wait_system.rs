@@ -50,7 +50,16 @@ impl<'a> System<'a> for WaitSystem {
                             rltk::to_cp437('♪'),
                         );
                     }
-                    WaitCause::Stun => {}
+                    WaitCause::Sleep => {
+                        particle_builder.request_aura(
+                            Point::new(position.x, position.y),
+                            MEDIUM_LIFETIME,
+                            rltk::RGB::named(rltk::LIGHT_BLUE),
+                            rltk::to_cp437('z'),
+                        );
+                    }
+                    WaitCause::Stun |
+                    WaitCause::Rest => {}
                 }
             }
         }